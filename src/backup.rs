@@ -0,0 +1,356 @@
+use crate::{
+    command::Command,
+    device::Device,
+    eeprom::{Eeprom, EEPROM_SIZE},
+    model::DeviceModel,
+    transport::Transport,
+    types::{CommandId, Error},
+};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/*
+EEPROM images are stored in the following format, multi byte values are big endian:
+┌─────────┬─────────┬───────────┬────────────┬───────────┬──────────────┬──────────────────┬───────────┬──────────┐
+│  Magic  │ Version │ Vendor ID │ Product ID │ Timestamp │ Firmware Len │ Firmware Version │ Data Len  │   Data   │
+│ 4 Bytes │ 1 Byte  │  2 Bytes  │  2 Bytes   │  8 Bytes  │    1 Byte    │ Firmware Len     │  4 Bytes  │ Data Len │
+└─────────┴─────────┴───────────┴────────────┴───────────┴──────────────┴──────────────────┴───────────┴──────────┘
+The timestamp is the number of seconds since the Unix epoch.
+*/
+
+/// Magic bytes starting every image file
+pub const IMAGE_MAGIC: [u8; 4] = *b"ATKE";
+/// Version of the image format written by [`EepromImage::to_bytes`]
+pub const IMAGE_VERSION: u8 = 1;
+
+/// A snapshot of the whole EEPROM of a mouse, from address 0x0 to [`EEPROM_SIZE`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EepromImage {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Raw data answered to `GetMouseVersion`
+    pub firmware_version: Vec<u8>,
+    /// When the snapshot was taken, with a precision of one second
+    pub timestamp: SystemTime,
+    pub data: Vec<u8>,
+}
+
+impl EepromImage {
+    /// Returns the model the image was taken from, if it is known.
+    pub fn model(&self) -> Option<&'static DeviceModel> {
+        DeviceModel::find(self.vendor_id, self.product_id)
+    }
+
+    /// Returns true if the image was taken from the same model as the device with the given IDs.
+    ///
+    /// The wired and receiver IDs of a known model are considered the same model.
+    pub fn matches(&self, vendor_id: u16, product_id: u16) -> bool {
+        if (self.vendor_id, self.product_id) == (vendor_id, product_id) {
+            return true;
+        }
+
+        match (self.model(), DeviceModel::find(vendor_id, product_id)) {
+            (Some(image), Some(device)) => image.name == device.name,
+            _ => false,
+        }
+    }
+
+    /// Encodes the image in the versioned file format.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(bytes)` with the encoded image.
+    /// * `Err(Error::DataTooLarge)` if the firmware version is longer than 255 bytes or the data longer
+    ///   than `u32::MAX` bytes, as their lengths would not fit in the header.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let firmware_len = u8::try_from(self.firmware_version.len())
+            .map_err(|_| Error::DataTooLarge(self.firmware_version.len()))?;
+        let data_len =
+            u32::try_from(self.data.len()).map_err(|_| Error::DataTooLarge(self.data.len()))?;
+        let timestamp = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        let mut raw = IMAGE_MAGIC.to_vec();
+        raw.push(IMAGE_VERSION);
+        raw.extend(self.vendor_id.to_be_bytes());
+        raw.extend(self.product_id.to_be_bytes());
+        raw.extend(timestamp.to_be_bytes());
+        raw.push(firmware_len);
+        raw.extend(&self.firmware_version);
+        raw.extend(data_len.to_be_bytes());
+        raw.extend(&self.data);
+
+        Ok(raw)
+    }
+
+    /// Decodes an image from the versioned file format.
+    pub fn from_bytes(raw: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader(raw);
+
+        if reader.take(IMAGE_MAGIC.len())? != IMAGE_MAGIC {
+            return Err(Error::ParseError("Not an EEPROM image".to_string()));
+        }
+        let version = reader.take(1)?[0];
+        if version != IMAGE_VERSION {
+            return Err(Error::ParseError(format!(
+                "Unsupported EEPROM image version: {}",
+                version
+            )));
+        }
+
+        let vendor_id = u16::from_be_bytes(reader.array()?);
+        let product_id = u16::from_be_bytes(reader.array()?);
+        let timestamp = UNIX_EPOCH + Duration::from_secs(u64::from_be_bytes(reader.array()?));
+        let firmware_len = reader.take(1)?[0] as usize;
+        let firmware_version = reader.take(firmware_len)?.to_vec();
+        let data_len = u32::from_be_bytes(reader.array()?) as usize;
+        let data = reader.take(data_len)?.to_vec();
+
+        if !reader.0.is_empty() {
+            return Err(Error::InvalidBufferLength {
+                expected: raw.len() - reader.0.len(),
+                actual: raw.len(),
+            });
+        }
+
+        Ok(Self {
+            vendor_id,
+            product_id,
+            firmware_version,
+            timestamp,
+            data,
+        })
+    }
+
+    /// Writes the image to a file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path, self.to_bytes()?).map_err(Error::IoError)
+    }
+
+    /// Reads an image from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        Self::from_bytes(&std::fs::read(path).map_err(Error::IoError)?)
+    }
+}
+
+/// Splits the fields of an image file, failing if the file is truncated.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < len {
+            return Err(Error::ParseError("Truncated EEPROM image".to_string()));
+        }
+
+        let (field, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(field)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the firmware version of the mouse with `GetMouseVersion`.
+    pub fn get_firmware_version(&self) -> Result<Vec<u8>, Error> {
+        let mut command = Command::<Eeprom>::default();
+        command.set_id(CommandId::GetMouseVersion);
+        let response = self.execute(command)?;
+
+        Ok(response.data()[..response.data_len()].to_vec())
+    }
+
+    /// Takes a snapshot of the whole EEPROM.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// # use libatk_rs::backup::EepromImage;
+    /// let backup = device.dump_eeprom().unwrap();
+    /// assert_eq!(EepromImage::from_bytes(&backup.to_bytes().unwrap()).unwrap(), backup);
+    ///
+    /// device.set_report_rate(ReportRate::Hz125).unwrap();
+    /// device.restore_eeprom(&backup, false).unwrap();
+    /// assert_eq!(device.dump_eeprom().unwrap().data, backup.data);
+    /// ```
+    pub fn dump_eeprom(&self) -> Result<EepromImage, Error> {
+        self.dump_eeprom_with_progress(|_, _| {})
+    }
+
+    /// Same as [`Device::dump_eeprom`], calling `progress` with the number of bytes read so far and the
+    /// total after each chunk.
+    pub fn dump_eeprom_with_progress(
+        &self,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<EepromImage, Error> {
+        let info = self.transport().device_info()?;
+        let firmware_version = self.get_firmware_version()?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let timestamp = UNIX_EPOCH + Duration::from_secs(now.as_secs());
+        let data = self.read_eeprom_with_progress(0x0, EEPROM_SIZE, progress)?;
+
+        Ok(EepromImage {
+            vendor_id: info.vendor_id,
+            product_id: info.product_id,
+            firmware_version,
+            timestamp,
+            data,
+        })
+    }
+
+    /// Writes a snapshot back to the EEPROM, verifying every chunk.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the whole image was written and verified.
+    /// * `Err(Error::ModelMismatch)` if the image was taken from another model and `force` is false.
+    /// * `Err(Error::VerificationFailed)` if a chunk read back differs from the image.
+    pub fn restore_eeprom(&self, image: &EepromImage, force: bool) -> Result<(), Error> {
+        self.restore_eeprom_with_progress(image, force, |_, _| {})
+    }
+
+    /// Same as [`Device::restore_eeprom`], calling `progress` with the number of bytes written so far and
+    /// the total after each chunk.
+    pub fn restore_eeprom_with_progress(
+        &self,
+        image: &EepromImage,
+        force: bool,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<(), Error> {
+        self.check_image(image, force)?;
+        self.write_eeprom_with_progress(0x0, &image.data, progress)
+    }
+
+    /// Checks that an image can be written to the device.
+    pub(crate) fn check_image(&self, image: &EepromImage, force: bool) -> Result<(), Error> {
+        let info = self.transport().device_info()?;
+        if !force && !image.matches(info.vendor_id, info.product_id) {
+            return Err(Error::ModelMismatch {
+                image: (image.vendor_id, image.product_id),
+                device: (info.vendor_id, info.product_id),
+            });
+        }
+        if image.data.len() > EEPROM_SIZE {
+            return Err(Error::DataTooLarge(image.data.len()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{simulator::Simulator, transport::DeviceInfo};
+
+    fn image() -> EepromImage {
+        EepromImage {
+            vendor_id: 0x3554,
+            product_id: 0xf58a,
+            firmware_version: vec![0x1, 0x2],
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            data: vec![0xaa; 4],
+        }
+    }
+
+    #[test]
+    fn images_use_the_file_format() {
+        assert_eq!(
+            image().to_bytes().unwrap(),
+            [
+                b'A', b'T', b'K', b'E', 0x1, 0x35, 0x54, 0xf5, 0x8a, 0x0, 0x0, 0x0, 0x0, 0x65,
+                0x53, 0xf1, 0x0, 0x2, 0x1, 0x2, 0x0, 0x0, 0x0, 0x4, 0xaa, 0xaa, 0xaa, 0xaa
+            ]
+        );
+        assert_eq!(
+            EepromImage::from_bytes(&image().to_bytes().unwrap()).unwrap(),
+            image()
+        );
+    }
+
+    #[test]
+    fn invalid_files_are_rejected() {
+        let raw = image().to_bytes().unwrap();
+
+        let mut bad_magic = raw.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            EepromImage::from_bytes(&bad_magic),
+            Err(Error::ParseError(message)) if message == "Not an EEPROM image"
+        ));
+
+        let mut unknown_version = raw.clone();
+        unknown_version[4] = IMAGE_VERSION + 1;
+        assert!(matches!(
+            EepromImage::from_bytes(&unknown_version),
+            Err(Error::ParseError(message)) if message == "Unsupported EEPROM image version: 2"
+        ));
+
+        for len in [0, 3, 10, raw.len() - 1] {
+            assert!(matches!(
+                EepromImage::from_bytes(&raw[..len]),
+                Err(Error::ParseError(message)) if message == "Truncated EEPROM image"
+            ));
+        }
+
+        let trailing = [raw.as_slice(), &[0x0]].concat();
+        assert!(matches!(
+            EepromImage::from_bytes(&trailing),
+            Err(Error::InvalidBufferLength { expected, actual })
+                if expected == raw.len() && actual == raw.len() + 1
+        ));
+    }
+
+    #[test]
+    fn oversized_firmware_versions_are_not_saved() {
+        let mut image = image();
+        image.firmware_version = vec![0x0; 255];
+        assert!(image.to_bytes().is_ok());
+
+        image.firmware_version.push(0x0);
+        assert!(matches!(image.to_bytes(), Err(Error::DataTooLarge(256))));
+    }
+
+    #[test]
+    fn images_of_other_models_need_force() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.transport().set_device_info(DeviceInfo {
+            vendor_id: 0x3554,
+            product_id: 0xf58e,
+            ..Default::default()
+        });
+        let mut image = device.dump_eeprom().unwrap();
+        image.vendor_id = 0x1234;
+        image.product_id = 0x5678;
+        image.data[0x4..0x6].copy_from_slice(&[0x3, 0x52]);
+
+        assert!(matches!(
+            device.restore_eeprom(&image, false),
+            Err(Error::ModelMismatch {
+                image: (0x1234, 0x5678),
+                device: (0x3554, 0xf58e)
+            })
+        ));
+        assert_eq!(device.transport().eeprom()[0x4..0x6], [0x0, 0x0]);
+
+        device.restore_eeprom(&image, true).unwrap();
+        assert_eq!(device.transport().eeprom()[..image.data.len()], image.data);
+    }
+
+    #[test]
+    fn wired_and_receiver_images_of_a_model_match() {
+        let image = image();
+
+        assert!(image.matches(0x3554, 0xf58a));
+        assert!(image.matches(0x3554, 0xf58b));
+        assert!(!image.matches(0x3554, 0xf58e));
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    command::Command,
+    device::Device,
+    eeprom::Eeprom,
+    transport::{ConnectionKind, Transport},
+    types::{CommandId, Error},
+};
+use std::ops::RangeInclusive;
+
+/*
+`GetBatteryLevel` is answered with:
+┌───────────┬──────────┬──────────────────────────────────────────────┐
+│   Level   │ Charging │                   Voltage                    │
+│ 0 - 100 % │  0 / 1   │ In tenths of a volt, 0 if it is not reported │
+└───────────┴──────────┴──────────────────────────────────────────────┘
+*/
+
+/// Plausible battery voltages in tenths of a volt
+static VOLTAGE_RANGE: RangeInclusive<u8> = 20..=50;
+
+/// Battery status of the mouse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryStatus {
+    pub percent: u8,
+    /// As reported by the firmware, see [`Device::connection`] to tell whether the mouse is on a cable
+    pub charging: bool,
+    /// Battery voltage in volts, `None` if the firmware does not report it
+    pub voltage: Option<f32>,
+}
+
+impl TryFrom<&[u8]> for BatteryStatus {
+    type Error = Error;
+
+    fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
+        let [percent, charging, voltage] = *raw else {
+            return Err(Error::InvalidBufferLength {
+                expected: 3,
+                actual: raw.len(),
+            });
+        };
+
+        if percent > 100 {
+            return Err(Error::ParseError(format!(
+                "Invalid battery level: {}",
+                percent
+            )));
+        }
+        let voltage = match voltage {
+            0 => None,
+            v if VOLTAGE_RANGE.contains(&v) => Some(v as f32 / 10f32),
+            v => return Err(Error::ParseError(format!("Invalid battery voltage: {}", v))),
+        };
+
+        Ok(Self {
+            percent,
+            charging: charging != 0,
+            voltage,
+        })
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the battery status of the mouse with `GetBatteryLevel`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BatteryStatus)` with the state of the battery.
+    /// * `Err(Error::MouseOffline)` if the device is a receiver and the mouse is not connected to it.
+    ///   Only a [`ConnectionKind::Dongle`] is asked whether the mouse is online.
+    /// * `Err(Error::ParseError)` if the reported level or voltage is out of range.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// let simulator = Simulator::<Eeprom>::new();
+    /// simulator.set_battery(80, false, 39);
+    /// let device = Device::from_transport(simulator);
+    ///
+    /// let status = device.get_battery_status().unwrap();
+    /// assert_eq!(status.percent, 80);
+    /// assert_eq!(status.voltage, Some(3.9));
+    ///
+    /// device.transport().set_mouse_online(false);
+    /// assert!(matches!(device.get_battery_status(), Err(Error::MouseOffline)));
+    /// ```
+    pub fn get_battery_status(&self) -> Result<BatteryStatus, Error> {
+        let mut command = Command::<Eeprom>::default();
+        command.set_id(CommandId::GetBatteryLevel);
+
+        // Some receivers answer with zeros rather than rejecting the query while the mouse is offline
+        let response = self.execute(command)?;
+        if response.data()[..3] == [0x0; 3]
+            && self.connection() == ConnectionKind::Dongle
+            && !self.is_mouse_online()?
+        {
+            return Err(Error::MouseOffline);
+        }
+
+        BatteryStatus::try_from(&response.data()[..3])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{simulator::Simulator, transport::DeviceInfo};
+
+    fn device(connection: ConnectionKind) -> Device<Simulator<Eeprom>> {
+        let device = Device::from_transport(Simulator::new());
+        device.transport().set_device_info(DeviceInfo {
+            connection,
+            ..Default::default()
+        });
+        device
+    }
+
+    fn online_checks_sent(device: &Device<Simulator<Eeprom>>) -> usize {
+        device
+            .transport()
+            .received()
+            .iter()
+            .filter(|report| report[1] == CommandId::GetWirelessMouseOnline as u8)
+            .count()
+    }
+
+    #[test]
+    fn wired_mice_report_the_firmware_charging_flag() {
+        let device = device(ConnectionKind::Wired);
+        device.transport().set_battery(100, false, 42);
+
+        let status = device.get_battery_status().unwrap();
+        assert_eq!(status.percent, 100);
+        assert!(!status.charging);
+        assert_eq!(status.voltage, Some(4.2));
+
+        // An empty battery on a cable is not mistaken for an offline mouse
+        device.transport().set_battery(0, false, 0);
+        let status = device.get_battery_status().unwrap();
+        assert_eq!((status.percent, status.voltage), (0, None));
+        assert_eq!(online_checks_sent(&device), 0);
+    }
+
+    #[test]
+    fn zeros_from_a_receiver_mean_the_mouse_is_offline() {
+        let device = device(ConnectionKind::Dongle);
+        device.transport().set_battery(0, false, 0);
+        device.transport().set_mouse_online(false);
+        // The receiver answers for the mouse with zeros instead of rejecting the query
+        let mut reply = Command::<Eeprom>::default();
+        reply.set_id(CommandId::GetBatteryLevel);
+        device.transport().inject_report(&reply.as_bytes());
+
+        assert!(matches!(
+            device.get_battery_status(),
+            Err(Error::MouseOffline)
+        ));
+        assert_eq!(online_checks_sent(&device), 1);
+    }
+
+    #[test]
+    fn unknown_connections_are_not_asked_whether_the_mouse_is_online() {
+        let device = device(ConnectionKind::Unknown);
+        device.transport().set_battery(0, false, 0);
+
+        let status = device.get_battery_status().unwrap();
+        assert_eq!((status.percent, status.voltage), (0, None));
+        assert_eq!(online_checks_sent(&device), 0);
+    }
+
+    #[test]
+    fn out_of_range_values_are_rejected() {
+        let device = device(ConnectionKind::Wired);
+
+        device.transport().set_battery(101, false, 42);
+        assert!(matches!(
+            device.get_battery_status(),
+            Err(Error::ParseError(_))
+        ));
+
+        for voltage in [19, 51] {
+            device.transport().set_battery(80, false, voltage);
+            assert!(matches!(
+                device.get_battery_status(),
+                Err(Error::ParseError(_))
+            ));
+        }
+    }
+}
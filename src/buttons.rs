@@ -0,0 +1,351 @@
+use crate::{
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+
+/*
+Each button is assigned a 4 byte slot, `Key0` to `Key15`:
+┌────────┬─────────────┬─────────────┬──────────┐
+│  Kind  │ Parameter 1 │ Parameter 2 │ Checksum │
+└────────┴─────────────┴─────────────┴──────────┘
+
+┌──────┬────────────────┬────────────────────┬──────────────────────────────────────────────┐
+│ Kind │ Action         │ Parameter 1        │ Parameter 2                                  │
+├──────┼────────────────┼────────────────────┼──────────────────────────────────────────────┤
+│ 0x00 │ Disabled       │ 0                  │ 0                                            │
+│ 0x01 │ Mouse button   │ 0                  │ 0x1 L, 0x2 R, 0x4 M, 0x8 back, 0x10 forward  │
+│ 0x02 │ DPI            │ 0                  │ 0x1 cycle, 0x2 up, 0x3 down                  │
+│ 0x03 │ Scroll         │ 0                  │ 0x1 up, 0x2 down                             │
+│ 0x04 │ Keyboard key   │ Modifier mask      │ HID usage code                               │
+│ 0x05 │ Media key      │ Usage [15:8]       │ Usage [7:0]                                  │
+│ 0x06 │ Macro          │ 0                  │ Macro index                                  │
+│ 0x07 │ Shortcut       │ 0                  │ Shortcut index                               │
+│ 0x08 │ Fire button    │ Interval in ms     │ Number of clicks                             │
+└──────┴────────────────┴────────────────────┴──────────────────────────────────────────────┘
+*/
+
+/// Number of button slots stored in the EEPROM
+pub const MAX_BUTTONS: usize = 16;
+/// Number of macro and shortcut slots a button can refer to
+pub const MAX_SLOTS: u8 = 16;
+
+static KEY_SLOTS: [EEPROMAddress; MAX_BUTTONS] = [
+    EEPROMAddress::Key0,
+    EEPROMAddress::Key1,
+    EEPROMAddress::Key2,
+    EEPROMAddress::Key3,
+    EEPROMAddress::Key4,
+    EEPROMAddress::Key5,
+    EEPROMAddress::Key6,
+    EEPROMAddress::Key7,
+    EEPROMAddress::Key8,
+    EEPROMAddress::Key9,
+    EEPROMAddress::Key10,
+    EEPROMAddress::Key11,
+    EEPROMAddress::Key12,
+    EEPROMAddress::Key13,
+    EEPROMAddress::Key14,
+    EEPROMAddress::Key15,
+];
+
+/// Keyboard modifier mask, using the bit layout of HID keyboard reports.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers(pub u8);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0x0);
+    pub const LEFT_CTRL: Modifiers = Modifiers(0x1);
+    pub const LEFT_SHIFT: Modifiers = Modifiers(0x2);
+    pub const LEFT_ALT: Modifiers = Modifiers(0x4);
+    pub const LEFT_GUI: Modifiers = Modifiers(0x8);
+    pub const RIGHT_CTRL: Modifiers = Modifiers(0x10);
+    pub const RIGHT_SHIFT: Modifiers = Modifiers(0x20);
+    pub const RIGHT_ALT: Modifiers = Modifiers(0x40);
+    pub const RIGHT_GUI: Modifiers = Modifiers(0x80);
+
+    /// Returns true if every modifier of `other` is set.
+    pub fn contains(&self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Modifiers;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for Modifiers {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Action assigned to a button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ButtonAction {
+    Disabled,
+    LeftClick,
+    RightClick,
+    MiddleClick,
+    Back,
+    Forward,
+    DpiCycle,
+    DpiUp,
+    DpiDown,
+    ScrollUp,
+    ScrollDown,
+    /// A keyboard key given by its HID usage code, pressed along with the modifiers
+    Key {
+        modifiers: Modifiers,
+        usage: u8,
+    },
+    /// A consumer control key given by its HID usage code, e.g. `0xcd` for play/pause
+    Media(u16),
+    /// Plays the macro stored in the given slot
+    Macro(u8),
+    /// Sends the key combination stored in the given slot
+    Shortcut(u8),
+    /// Repeats a left click `count` times, `interval` milliseconds apart
+    FireButton {
+        interval: u8,
+        count: u8,
+    },
+}
+
+impl ButtonAction {
+    /// Encodes the action into its EEPROM slot, including the checksum.
+    pub fn as_bytes(&self) -> [u8; 4] {
+        let [kind, param1, param2] = match *self {
+            ButtonAction::Disabled => [0x0, 0x0, 0x0],
+            ButtonAction::LeftClick => [0x1, 0x0, 0x1],
+            ButtonAction::RightClick => [0x1, 0x0, 0x2],
+            ButtonAction::MiddleClick => [0x1, 0x0, 0x4],
+            ButtonAction::Back => [0x1, 0x0, 0x8],
+            ButtonAction::Forward => [0x1, 0x0, 0x10],
+            ButtonAction::DpiCycle => [0x2, 0x0, 0x1],
+            ButtonAction::DpiUp => [0x2, 0x0, 0x2],
+            ButtonAction::DpiDown => [0x2, 0x0, 0x3],
+            ButtonAction::ScrollUp => [0x3, 0x0, 0x1],
+            ButtonAction::ScrollDown => [0x3, 0x0, 0x2],
+            ButtonAction::Key { modifiers, usage } => [0x4, modifiers.0, usage],
+            ButtonAction::Media(usage) => {
+                let [high, low] = usage.to_be_bytes();
+                [0x5, high, low]
+            }
+            ButtonAction::Macro(index) => [0x6, 0x0, index],
+            ButtonAction::Shortcut(index) => [0x7, 0x0, index],
+            ButtonAction::FireButton { interval, count } => [0x8, interval, count],
+        };
+
+        let raw = with_checksum(&[kind, param1, param2]);
+        [raw[0], raw[1], raw[2], raw[3]]
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        match *self {
+            ButtonAction::Macro(index) | ButtonAction::Shortcut(index) if index >= MAX_SLOTS => {
+                Err(Error::InvalidSetting(format!(
+                    "Slot index must be below {}: {}",
+                    MAX_SLOTS, index
+                )))
+            }
+            ButtonAction::FireButton { count: 0, .. } => Err(Error::InvalidSetting(
+                "Fire button needs at least one click".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for ButtonAction {
+    type Error = Error;
+
+    fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
+        if raw.len() != 4 {
+            return Err(Error::InvalidBufferLength {
+                expected: 4,
+                actual: raw.len(),
+            });
+        }
+
+        let action = match *verify_checksum(raw)? {
+            [0x0, _, _] => ButtonAction::Disabled,
+            [0x1, _, 0x1] => ButtonAction::LeftClick,
+            [0x1, _, 0x2] => ButtonAction::RightClick,
+            [0x1, _, 0x4] => ButtonAction::MiddleClick,
+            [0x1, _, 0x8] => ButtonAction::Back,
+            [0x1, _, 0x10] => ButtonAction::Forward,
+            [0x2, _, 0x1] => ButtonAction::DpiCycle,
+            [0x2, _, 0x2] => ButtonAction::DpiUp,
+            [0x2, _, 0x3] => ButtonAction::DpiDown,
+            [0x3, _, 0x1] => ButtonAction::ScrollUp,
+            [0x3, _, 0x2] => ButtonAction::ScrollDown,
+            [0x4, modifiers, usage] => ButtonAction::Key {
+                modifiers: Modifiers(modifiers),
+                usage,
+            },
+            [0x5, high, low] => ButtonAction::Media(u16::from_be_bytes([high, low])),
+            [0x6, _, index] => ButtonAction::Macro(index),
+            [0x7, _, index] => ButtonAction::Shortcut(index),
+            [0x8, interval, count] => ButtonAction::FireButton { interval, count },
+            _ => {
+                return Err(Error::ParseError(format!(
+                    "Invalid button action: {:02X?}",
+                    raw
+                )))
+            }
+        };
+
+        Ok(action)
+    }
+}
+
+impl<T: Transport> Device<T> {
+    fn button_count(&self) -> usize {
+        self.model()
+            .map_or(MAX_BUTTONS, |model| model.buttons as usize)
+    }
+
+    fn check_button_index(&self, index: usize) -> Result<(), Error> {
+        if index >= self.button_count() {
+            return Err(Error::InvalidSetting(format!(
+                "Button index must be below {}: {}",
+                self.button_count(),
+                index
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Reads the action assigned to the button at `index`.
+    pub fn get_button(&self, index: usize) -> Result<ButtonAction, Error> {
+        self.check_button_index(index)?;
+        ButtonAction::try_from(self.get_eeprom(KEY_SLOTS[index], 4)?.as_slice())
+    }
+
+    /// Assigns an action to the button at `index`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the action was written.
+    /// * `Err(Error::InvalidSetting)` if the index is out of range for the model or the action refers to a
+    ///   nonexistent macro or shortcut slot.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::buttons::{ButtonAction, Modifiers};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let copy = ButtonAction::Key { modifiers: Modifiers::LEFT_CTRL, usage: 0x06 };
+    /// device.set_button(3, copy).unwrap();
+    /// assert_eq!(device.get_button(3).unwrap(), copy);
+    /// ```
+    pub fn set_button(&self, index: usize, action: ButtonAction) -> Result<(), Error> {
+        self.check_button_index(index)?;
+        action.validate()?;
+
+        self.set_eeprom(KEY_SLOTS[index], &action.as_bytes())
+    }
+
+    /// Reads the actions assigned to every button of the device.
+    ///
+    /// Returns all [`MAX_BUTTONS`] slots if the model of the device is unknown.
+    pub fn get_all_buttons(&self) -> Result<Vec<ButtonAction>, Error> {
+        let count = self.button_count();
+        let mut actions = Vec::with_capacity(count);
+
+        // Two slots fit in a single command
+        for index in (0..count).step_by(2) {
+            let len = if count - index > 1 { 8 } else { 4 };
+            for slot in self.get_eeprom(KEY_SLOTS[index], len)?.chunks(4) {
+                actions.push(ButtonAction::try_from(slot)?);
+            }
+        }
+
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator};
+
+    /// Actions along with their hand-encoded slots
+    fn slots() -> Vec<(ButtonAction, [u8; 4])> {
+        vec![
+            (ButtonAction::Disabled, [0x0, 0x0, 0x0, 0x55]),
+            (ButtonAction::LeftClick, [0x1, 0x0, 0x1, 0x53]),
+            (ButtonAction::Forward, [0x1, 0x0, 0x10, 0x44]),
+            (ButtonAction::DpiDown, [0x2, 0x0, 0x3, 0x50]),
+            (ButtonAction::ScrollDown, [0x3, 0x0, 0x2, 0x50]),
+            (
+                ButtonAction::Key {
+                    modifiers: Modifiers::LEFT_CTRL,
+                    usage: 0x06,
+                },
+                [0x4, 0x1, 0x6, 0x4a],
+            ),
+            (ButtonAction::Media(0x0cd), [0x5, 0x0, 0xcd, 0x83]),
+            (ButtonAction::Media(0x223), [0x5, 0x2, 0x23, 0x2b]),
+            (ButtonAction::Macro(3), [0x6, 0x0, 0x3, 0x4c]),
+            (ButtonAction::Shortcut(15), [0x7, 0x0, 0xf, 0x3f]),
+            (
+                ButtonAction::FireButton {
+                    interval: 20,
+                    count: 3,
+                },
+                [0x8, 0x14, 0x3, 0x36],
+            ),
+        ]
+    }
+
+    #[test]
+    fn slots_use_the_atk_layout() {
+        for (action, raw) in slots() {
+            assert_eq!(action.as_bytes(), raw, "{:?}", action);
+            assert_eq!(ButtonAction::try_from(raw.as_slice()).unwrap(), action);
+        }
+    }
+
+    #[test]
+    fn buttons_are_written_to_their_key_slot() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        for (index, (action, _)) in slots().into_iter().enumerate() {
+            device.set_button(index, action).unwrap();
+        }
+
+        let eeprom = device.transport().eeprom();
+        for (index, (action, raw)) in slots().into_iter().enumerate() {
+            let offset = 0x60 + index * 4;
+            assert_eq!(eeprom[offset..offset + 4], raw);
+            assert_eq!(device.get_button(index).unwrap(), action);
+        }
+    }
+
+    #[test]
+    fn invalid_slots_are_rejected() {
+        assert!(ButtonAction::try_from([0x9, 0x0, 0x0, 0x4c].as_slice()).is_err());
+        assert!(matches!(
+            ButtonAction::try_from([0x1, 0x0, 0x1, 0x54].as_slice()),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        assert!(device
+            .set_button(0, ButtonAction::Macro(MAX_SLOTS))
+            .is_err());
+        assert!(device
+            .set_button(MAX_BUTTONS, ButtonAction::LeftClick)
+            .is_err());
+    }
+}
@@ -0,0 +1,49 @@
+/// A strategy for computing the checksum bytes used by the ATK protocol.
+///
+/// Two kinds of checksums show up in the protocol: the one closing every command frame and the
+/// ones stored next to values in the EEPROM (e.g. `ReportRate` / `ReportRateCrc`). Firmwares
+/// differ in the seed and scheme they use, so the strategy is selected per
+/// [`CommandDescriptor`](crate::command::CommandDescriptor).
+pub trait Checksum {
+    /// Computes the checksum closing a frame.
+    ///
+    /// `report` holds the report ID followed by every byte of the command except the checksum itself.
+    fn frame(&self, report: &[u8]) -> u8;
+
+    /// Computes the checksum stored after a group of EEPROM value bytes.
+    fn value(&self, bytes: &[u8]) -> u8;
+}
+
+/// Checksum computed as the seed minus the 8-bit sum of the bytes.
+///
+/// This is the scheme used by most ATK mice, with a seed of `0x55`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubtractChecksum {
+    pub seed: u8,
+}
+
+impl SubtractChecksum {
+    /// The scheme used by default, `0x55` minus the 8-bit sum
+    pub const ATK: SubtractChecksum = SubtractChecksum { seed: 0x55 };
+
+    fn compute(&self, bytes: &[u8]) -> u8 {
+        let sum = bytes.iter().fold(0u8, |acc, &byte| acc.wrapping_add(byte));
+        self.seed.wrapping_sub(sum)
+    }
+}
+
+/// Index of the high byte of the big-endian EEPROM address in a report
+static ADDRESS_HIGH_BYTE: usize = 0x3;
+
+impl Checksum for SubtractChecksum {
+    /// The firmware adds the EEPROM address as a 16-bit value, so only its low byte counts towards the
+    /// 8-bit sum.
+    fn frame(&self, report: &[u8]) -> u8 {
+        let high_byte = report.get(ADDRESS_HIGH_BYTE).copied().unwrap_or(0);
+        self.compute(report).wrapping_add(high_byte)
+    }
+
+    fn value(&self, bytes: &[u8]) -> u8 {
+        self.compute(bytes)
+    }
+}
@@ -1,14 +1,25 @@
-use crate::device::REPORT_ID;
+use crate::checksum::{Checksum, SubtractChecksum};
 use crate::types::{CommandId, EEPROMAddress, Error};
 
-// These are hardcoded for now as i don't know if there are other devices with different values.
-// If that is the case then these can be set dynamically
-/// Represents the offset from the start of the command to the first byte of the data field
-static BASE_OFFSET: usize = 0x5;
-static CMD_LEN: usize = 0x10;
-
 /// A trait that allows to define new commands
-pub trait CommandDescriptor {}
+///
+/// The associated constants describe the frame layout and checksum scheme of the command. The
+/// defaults match the 16 byte frames used by most ATK devices, so `#[derive(Command)]` is enough for
+/// those. Devices with a different layout can implement the trait by hand and override the constants.
+pub trait CommandDescriptor {
+    /// Offset from the start of the command to the first byte of the data field
+    const BASE_OFFSET: usize = 0x5;
+    /// Report ID prepended to the command when it is sent to the device
+    const REPORT_ID: u8 = 0x8;
+    /// Length of the command in bytes, excluding the report ID
+    const CMD_LEN: usize = 0x10;
+
+    /// Maximum number of data bytes that fit in the command
+    const MAX_DATA_LEN: usize = Self::CMD_LEN - Self::BASE_OFFSET - 1;
+
+    /// Checksum scheme used for the frame and for EEPROM values
+    const CHECKSUM: &'static dyn Checksum = &SubtractChecksum::ATK;
+}
 
 /*
 The command layout is as follows:
@@ -73,7 +84,7 @@ impl<T: CommandDescriptor> Default for Command<T> {
             status: 0,
             eeprom_address: EEPROMAddress::ReportRate,
             data_len: 0,
-            data: vec![0u8; CMD_LEN - BASE_OFFSET - 1],
+            data: vec![0u8; T::MAX_DATA_LEN],
             checksum: 0,
             _cmd: std::marker::PhantomData,
         }
@@ -92,19 +103,28 @@ impl<T: CommandDescriptor> TryFrom<&[u8]> for Command<T> {
     type Error = Error;
 
     fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
-        if raw.len() != CMD_LEN {
+        if raw.len() != T::CMD_LEN {
             return Err(Error::InvalidBufferLength {
-                expected: CMD_LEN,
+                expected: T::CMD_LEN,
                 actual: raw.len(),
             });
         }
 
+        let expected = T::CHECKSUM.frame(&[&[T::REPORT_ID], &raw[..T::CMD_LEN - 1]].concat());
+        let actual = raw[T::CMD_LEN - 1];
+        if expected != actual {
+            return Err(Error::ChecksumMismatch { expected, actual });
+        }
+
         let command_id = raw[0x0].try_into()?;
         let status = raw[0x1];
-        let eeprom_address = u16::from_be_bytes([raw[0x2], raw[0x3]]).try_into()?;
+        let eeprom_address = u16::from_be_bytes([raw[0x2], raw[0x3]]).into();
         let data_len = raw[0x4] as usize;
-        let data = raw[BASE_OFFSET..BASE_OFFSET + data_len].to_vec();
-        let checksum = raw[0xf];
+        if data_len > T::MAX_DATA_LEN {
+            return Err(Error::DataTooLarge(data_len));
+        }
+        let data = raw[T::BASE_OFFSET..T::CMD_LEN - 1].to_vec();
+        let checksum = actual;
 
         Ok(Self {
             command_id,
@@ -156,7 +176,7 @@ impl<T: CommandDescriptor> Command<T> {
     /// * `Ok(())` if the value is successfully set.
     /// * `Err(Error::InvalidOffset)` if the offset is out of bounds.
     pub fn set_data_byte(&mut self, value: u8, offset: usize) -> Result<(), Error> {
-        if offset > self.data_len() - 1 {
+        if offset >= self.data_len() {
             return Err(Error::InvalidOffset(offset));
         }
 
@@ -168,7 +188,7 @@ impl<T: CommandDescriptor> Command<T> {
     /// Sets a byte value at an even offset and also writes its complementary checksum byte.
     ///
     /// This method first writes the provided `value` at the given *even* `offset` and then writes
-    /// the checksum (calculated as `0x55.warpping_sum(value)`) at the following byte.
+    /// its checksum (calculated by the descriptor's [`Checksum`] scheme) at the following byte.
     ///
     /// # Arguments
     ///
@@ -185,7 +205,7 @@ impl<T: CommandDescriptor> Command<T> {
         }
 
         self.set_data_byte(value, offset)?;
-        self.set_data_byte(0x55u8.wrapping_sub(value), offset + 1)?;
+        self.set_data_byte(T::CHECKSUM.value(&[value]), offset + 1)?;
 
         Ok(())
     }
@@ -204,6 +224,14 @@ impl<T: CommandDescriptor> Command<T> {
         self.status
     }
 
+    /// Returns true if the status byte of a response reports success.
+    ///
+    /// The device answers with a status of `0x0` when it accepted the command and any other value
+    /// when it rejected it.
+    pub fn is_success(&self) -> bool {
+        self.status == 0x0
+    }
+
     /// Sets the status byte and updates the checksum afterward.
     pub fn set_status(&mut self, status: u8) {
         self.status = status;
@@ -228,12 +256,12 @@ impl<T: CommandDescriptor> Command<T> {
 
     /// Sets the valid data length.
     ///
-    /// # Panics
+    /// # Errors
     ///
-    /// Panics if the provided length exceeds the maximum available space computed via:
-    /// `CMD_LEN - BASE_OFFSET`
+    /// Returns `Err(Error::DataTooLarge)` if the provided length exceeds the maximum available space
+    /// computed via: `CMD_LEN - BASE_OFFSET - 1`
     pub fn set_data_len(&mut self, len: usize) -> Result<(), Error> {
-        if len as usize > CMD_LEN - BASE_OFFSET {
+        if len > T::MAX_DATA_LEN {
             return Err(Error::DataTooLarge(len));
         }
 
@@ -243,17 +271,11 @@ impl<T: CommandDescriptor> Command<T> {
     }
 
     fn set_checksum(&mut self) {
-        let sum: u8 = {
-            let mut sum = REPORT_ID as u16;
-            sum += self.command_id as u16;
-            sum += self.status as u16;
-            sum += self.eeprom_address as u16;
-            sum += self.data_len as u16;
-            sum += self.data.iter().fold(0, |acc, &byte| acc + byte as u16);
-            (sum & 0xff) as u8
-        };
-        let checksum = 0x55u8.wrapping_sub(sum);
-        self.checksum = checksum;
+        let mut raw = self.as_bytes();
+        // Drop the stale checksum and prepend the report ID
+        raw.pop();
+        raw.insert(0, T::REPORT_ID);
+        self.checksum = T::CHECKSUM.frame(&raw);
     }
 
     /// Serializes the command into a vector of bytes.
@@ -263,21 +285,21 @@ impl<T: CommandDescriptor> Command<T> {
     /// 2. Status
     /// 3. EEPROM address as big-endian bytes
     /// 4. Valid data length
-    /// 5. Data payload
-    /// 6. Checksum
+    /// 5. Data payload, starting at `BASE_OFFSET`
+    /// 6. Checksum as the last byte
     ///
     /// # Returns
     ///
     /// A vector containing the bytewise representation of the command.
     pub fn as_bytes(&self) -> Vec<u8> {
-        let mut raw = vec![self.command_id as u8, self.status];
-        raw.extend_from_slice(&(self.eeprom_address as u16).to_be_bytes());
-        raw.push(self.data_len as u8);
-        raw.extend_from_slice(&self.data);
-        // Pad the remaining bytes with zeroes
-        let remaining = CMD_LEN - raw.len() - 1;
-        raw.extend_from_slice(&vec![0u8; remaining]);
-        raw.push(self.checksum);
+        // Fields not covered below stay zero padded
+        let mut raw = vec![0u8; T::CMD_LEN];
+        raw[0x0] = self.command_id as u8;
+        raw[0x1] = self.status;
+        raw[0x2..0x4].copy_from_slice(&self.eeprom_address.value().to_be_bytes());
+        raw[0x4] = self.data_len as u8;
+        raw[T::BASE_OFFSET..T::BASE_OFFSET + self.data.len()].copy_from_slice(&self.data);
+        raw[T::CMD_LEN - 1] = self.checksum;
         raw
     }
 
@@ -290,16 +312,35 @@ impl<T: CommandDescriptor> Command<T> {
         &self.data
     }
 
+    /// Returns true if the raw report is the response to this command.
+    ///
+    /// A response must carry the same command ID. Responses to EEPROM commands must also carry the
+    /// same EEPROM address, other commands don't use the address field.
+    pub fn is_answered_by(&self, raw: &[u8]) -> bool {
+        if raw.len() < T::BASE_OFFSET || raw[0x0] != self.command_id as u8 {
+            return false;
+        }
+
+        match self.command_id {
+            CommandId::SetEEPROM | CommandId::GetEEPROM => {
+                raw[0x2..0x4] == self.eeprom_address.value().to_be_bytes()
+            }
+            _ => true,
+        }
+    }
+
     /// Executes the command on the specified device.
     ///
+    /// See [`Device::execute`](crate::device::Device::execute) for how responses are matched.
+    ///
     /// # Returns
     ///
     /// Response of the command
-    pub fn execute(&self, device: &crate::device::Device) -> Result<Command<T>, Error> {
-        device.send(self)?;
-
-        let response = device.read()?;
-        Command::try_from(response)
+    pub fn execute<D: crate::transport::Transport>(
+        &self,
+        device: &crate::device::Device<D>,
+    ) -> Result<Command<T>, Error> {
+        device.execute(self.clone())
     }
 }
 
@@ -316,3 +357,21 @@ impl<T: CommandDescriptor> CommandBuilder<T> {
         self.command
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eeprom::Eeprom;
+
+    #[test]
+    fn frame_checksum_adds_the_address_as_u16() {
+        let command = Command::<Eeprom>::get(EEPROMAddress::from(0x0110), 2).unwrap();
+
+        // 0x55 - (0x08 + 0x08 + 0x0110 + 0x02) truncated to 8 bits
+        let mut expected = vec![0x08, 0x00, 0x01, 0x10, 0x02];
+        expected.resize(0xf, 0x0);
+        expected.push(0x33);
+        assert_eq!(command.as_bytes(), expected);
+        assert!(Command::<Eeprom>::try_from(expected.as_slice()).is_ok());
+    }
+}
@@ -1,61 +1,108 @@
 use crate::{
     command::{Command, CommandDescriptor},
+    model::DeviceModel,
+    transport::{ConnectionKind, DeviceInfo, HidTransport, Transport},
     types::Error,
 };
-use hidapi::HidDevice;
+use std::collections::VecDeque;
+use std::ffi::CString;
+use std::sync::Mutex;
+use std::time::Duration;
 
-pub static REPORT_ID: u8 = 0x8;
-static MAX_REPORT_LENGTH: usize = 64;
+/// Largest HID report (64 bytes) plus the report ID
+static MAX_REPORT_LENGTH: usize = 65;
+/// Number of unsolicited reports kept around, older reports are dropped first
+static MAX_QUEUED_REPORTS: usize = 32;
+/// Number of non matching reports skipped while waiting for a response
+static MAX_SKIPPED_REPORTS: usize = 16;
+/// Timeout used for reads unless configured otherwise
+static DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Controls how [`Device::execute`] retries a command that did not get a valid response.
+///
+/// A command is retried when reading the response times out or when the response fails the
+/// checksum verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub attempts: u32,
+    /// Delay between two attempts
+    pub backoff: Duration,
+    /// Send the command again after a timeout instead of only waiting longer for the response
+    pub resend_on_timeout: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub const NONE: RetryPolicy = RetryPolicy {
+        attempts: 1,
+        backoff: Duration::ZERO,
+        resend_on_timeout: false,
+    };
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            backoff: Duration::from_millis(50),
+            resend_on_timeout: true,
+        }
+    }
+}
 
 /// A wrapper around a HID device that simplifies communication by exposing functionality for sending commands
 /// and reading responses.
 ///
-/// The Device struct encapsulates a [hidapi::HidDevice] and provides methods to send commands with their specific
-/// report IDs and read responses from the device (while handling the report ID in the data).
+/// The Device struct encapsulates a [`Transport`] (a [hidapi::HidDevice] by default) and provides methods to send
+/// commands with their specific report IDs and read responses from the device (while handling the report ID in
+/// the data).
 ///
 /// # Examples
 ///
 /// ```no_run
+/// # use libatk_rs::prelude::*;
+/// # #[derive(Command)]
+/// # struct Query;
 /// // Create a new device by specifying vendor id, product id, usage page and usage.
 /// let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01)
 ///     .expect("Device not found or failed to open");
 ///
 /// // Create your command according to your custom CommandDescriptor implementation:
-/// let command = Command::new(...);
+/// let command = Command::<Query>::default();
 ///
 /// // Send command to the device.
-/// device.send(command).expect("Failed to send command");
+/// device.send(&command).expect("Failed to send command");
 ///
 /// // Read the response from the device.
 /// let response = device.read().expect("Failed to read response");
 /// println!("Response: {:?}", response);
 /// ```
 #[derive(Debug)]
-pub struct Device(HidDevice);
-
-impl std::ops::Deref for Device {
-    type Target = HidDevice;
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+pub struct Device<T: Transport = HidTransport> {
+    transport: T,
+    /// Reports received while waiting for the response of another command
+    queued: Mutex<VecDeque<Vec<u8>>>,
+    timeout: Option<Duration>,
+    retry_policy: RetryPolicy,
+    model: Option<&'static DeviceModel>,
 }
 
-impl std::fmt::Display for Device {
+impl<T: Transport> std::fmt::Display for Device<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let info = self.0.get_device_info().unwrap();
-        let product_string = info.product_string().unwrap();
-        let manufacturer_string = info.manufacturer_string().unwrap();
-        let serial_number_string = info.serial_number().unwrap();
-        let path = info.path().to_str().unwrap();
+        let info = self.transport.device_info().map_err(|_| std::fmt::Error)?;
         write!(
             f,
             "Device: {}\nManufacturer: {}\nSerial Number: {}\nPath: {}",
-            product_string, manufacturer_string, serial_number_string, path
+            info.product.unwrap_or_default(),
+            info.manufacturer.unwrap_or_default(),
+            info.serial_number.unwrap_or_default(),
+            info.path
         )
     }
 }
 
-impl Device {
+impl Device<HidTransport> {
     /// Creates a new Device instance by searching for a HID device matching the given vendor id, product id,
     /// usage page, and usage.
     ///
@@ -73,6 +120,7 @@ impl Device {
     ///
     /// # Examples
     /// ```no_run
+    /// # use libatk_rs::prelude::*;
     /// let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01)
     ///     .expect("Failed to open device");
     /// ```
@@ -82,17 +130,16 @@ impl Device {
         usage_page: u16,
         usage: u16,
     ) -> Result<Self, Error> {
-        let context = hidapi::HidApi::new().unwrap();
+        let context = hidapi::HidApi::new().map_err(Error::HidError)?;
 
         let device = context
             .device_list()
-            .filter(|&d| {
+            .find(|&d| {
                 d.product_id() == product_id
                     && d.vendor_id() == vendor_id
                     && d.usage_page() == usage_page
                     && d.usage() == usage
             })
-            .next()
             .ok_or(Error::HidError(hidapi::HidError::HidApiError {
                 message: format!(
                     "Device not found: vendor_id={} product_id={} usage_page={} usage={}",
@@ -100,17 +147,132 @@ impl Device {
                 ),
             }))?;
 
-        Ok(Device(
-            device
-                .open_device(&context)
-                .map_err(|e| Error::HidError(e))?,
-        ))
+        let device = device.open_device(&context).map_err(Error::HidError)?;
+
+        Ok(Device::from_transport(HidTransport::new(device)))
+    }
+
+    /// Lists every attached interface that speaks the ATK protocol.
+    ///
+    /// See [`DeviceInfo::is_atk_interface`] for how interfaces are selected. A mouse plugged in with a
+    /// cable and its receiver show up as separate entries.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// for info in Device::enumerate().expect("Failed to list devices") {
+    ///     println!("{:04x}:{:04x} {:?} at {}", info.vendor_id, info.product_id, info.connection, info.path);
+    /// }
+    /// ```
+    pub fn enumerate() -> Result<Vec<DeviceInfo>, Error> {
+        let context = hidapi::HidApi::new().map_err(Error::HidError)?;
+
+        Ok(context
+            .device_list()
+            .map(DeviceInfo::from)
+            .filter(DeviceInfo::is_atk_interface)
+            .collect())
+    }
+
+    /// Opens the device at the given platform specific path, e.g. `/dev/hidraw3` on Linux.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// let infos = Device::enumerate().expect("Failed to list devices");
+    /// let device = Device::open_path(&infos[0].path).expect("Failed to open device");
+    /// ```
+    pub fn open_path(path: &str) -> Result<Self, Error> {
+        let context = hidapi::HidApi::new().map_err(Error::HidError)?;
+        let path = CString::new(path).map_err(|e| Error::ParseError(e.to_string()))?;
+        let device = context.open_path(&path).map_err(Error::HidError)?;
+
+        Ok(Device::from_transport(HidTransport::new(device)))
+    }
+
+    /// Opens the ATK interface of the device with the given serial number.
+    pub fn open_serial(serial_number: &str) -> Result<Self, Error> {
+        let info = Self::enumerate()?
+            .into_iter()
+            .find(|info| info.serial_number.as_deref() == Some(serial_number))
+            .ok_or(Error::HidError(hidapi::HidError::HidApiError {
+                message: format!("Device not found: serial_number={}", serial_number),
+            }))?;
+
+        Self::open_path(&info.path)
+    }
+
+    /// Opens the first attached device found in the known device registry.
+    ///
+    /// The [`DeviceModel`] of the device is attached, see [`Device::model`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// let device = Device::open_any().expect("No supported device found");
+    /// println!("Found {}", device.model().unwrap().name);
+    /// ```
+    pub fn open_any() -> Result<Self, Error> {
+        let info = Self::enumerate()?
+            .into_iter()
+            .find(|info| DeviceModel::find(info.vendor_id, info.product_id).is_some())
+            .ok_or(Error::HidError(hidapi::HidError::HidApiError {
+                message: "No supported device found".to_string(),
+            }))?;
+
+        Self::open_path(&info.path)
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Creates a new Device instance communicating over the given transport.
+    ///
+    /// The [`DeviceModel`] is looked up from the vendor and product IDs reported by the transport.
+    pub fn from_transport(transport: T) -> Self {
+        let model = transport
+            .device_info()
+            .ok()
+            .and_then(|info| DeviceModel::find(info.vendor_id, info.product_id));
+
+        Self {
+            transport,
+            queued: Mutex::new(VecDeque::new()),
+            timeout: Some(DEFAULT_TIMEOUT),
+            retry_policy: RetryPolicy::default(),
+            model,
+        }
+    }
+
+    /// Returns the model of the device, if it is a known one.
+    pub fn model(&self) -> Option<&'static DeviceModel> {
+        self.model
+    }
+
+    /// Overrides the model of the device, e.g. for devices missing from the registry.
+    pub fn set_model(&mut self, model: Option<&'static DeviceModel>) {
+        self.model = model;
+    }
+
+    /// Returns how the device is connected, taken from its model or else from the transport.
+    pub fn connection(&self) -> ConnectionKind {
+        match self.model {
+            Some(model) => model.connection,
+            None => self
+                .transport
+                .device_info()
+                .map_or(ConnectionKind::Unknown, |info| info.connection),
+        }
+    }
+
+    /// Returns the transport used to communicate with the device.
+    pub fn transport(&self) -> &T {
+        &self.transport
     }
 
     /// Sends a command to the device.
     ///
-    /// This function takes a command that implements the CommandDescriptor trait, prepends the report ID,
-    /// and sends the complete command over the HID interface.
+    /// This function takes a command that implements the CommandDescriptor trait, prepends the report ID
+    /// defined by the descriptor, and sends the complete command over the HID interface.
     ///
     /// # Type Parameters
     ///
@@ -123,60 +285,432 @@ impl Device {
     /// # Returns
     ///
     /// * `Ok(usize)` indicating the number of bytes written if the write operation is successful.
-    /// * `Err(hidapi::HidError)` if the write operation fails.
+    /// * `Err(Error)` if the transport fails to write the report.
     ///
     /// # Examples
     /// ```no_run
-    /// let bytes_written = device.send(command).expect("Failed to send command");
+    /// # use libatk_rs::prelude::*;
+    /// # #[derive(Command)]
+    /// # struct Query;
+    /// # let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01).unwrap();
+    /// # let command = Command::<Query>::default();
+    /// let bytes_written = device.send(&command).expect("Failed to send command");
     /// println!("Bytes written: {}", bytes_written);
     /// ```
-    pub fn send<T: CommandDescriptor>(&self, command: &Command<T>) -> Result<usize, Error> {
+    pub fn send<C: CommandDescriptor>(&self, command: &Command<C>) -> Result<usize, Error> {
         // Prepend Report ID to the command
-        let data = [[REPORT_ID].as_ref(), command.as_bytes().as_ref()].concat();
-        self.0.write(&data).map_err(|e| Error::HidError(e))
+        let data = [[C::REPORT_ID].as_ref(), command.as_bytes().as_ref()].concat();
+        self.transport.write_report(&data)
     }
 
-    /// Reads data from the device.
+    /// Returns the timeout used by [`Device::read`] and [`Device::execute`].
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the timeout used by [`Device::read`] and [`Device::execute`], `None` blocks until a report arrives.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Returns the retry policy used by [`Device::execute`].
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Sets the retry policy used by [`Device::execute`].
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    /// Reads data from the device using the default timeout of the device.
     ///
-    /// This method reads a report from the underlying HID device into a fixed-size buffer, strips off the first byte
-    /// (which is assumed to be the Report ID), and returns the remaining bytes as a vector.
+    /// This method returns the oldest report queued by [`Device::execute`] if there is one. Otherwise it reads
+    /// a report from the underlying HID device into a fixed-size buffer, strips off the first byte (which is
+    /// assumed to be the Report ID), and returns the remaining bytes as a vector.
     ///
     /// # Returns
     ///
     /// * `Ok(Vec<u8>)` containing the data read from the device (without the report ID) if successful.
-    /// * `Err(hidapi::HidError)` if the read operation fails.
+    /// * `Err(Error::Timeout)` if no report arrived before the timeout.
+    /// * `Err(Error)` if the transport fails to read the report.
     ///
     /// # Examples
     /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// # let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01).unwrap();
     /// let response = device.read().expect("Failed to read from device");
     /// println!("Response: {:?}", response);
     /// ```
     pub fn read(&self) -> Result<Vec<u8>, Error> {
+        if let Some(report) = self.queued.lock().unwrap().pop_front() {
+            return Ok(report);
+        }
+
+        self.read_report(self.timeout)
+    }
+
+    /// Reads data from the device, waiting at most `timeout` for a report.
+    ///
+    /// Behaves like [`Device::read`] otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// # use std::time::Duration;
+    /// # let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01).unwrap();
+    /// match device.read_timeout(Duration::from_millis(200)) {
+    ///     Ok(response) => println!("Response: {:?}", response),
+    ///     Err(Error::Timeout) => println!("The device is asleep"),
+    ///     Err(e) => panic!("Failed to read from device: {}", e),
+    /// }
+    /// ```
+    pub fn read_timeout(&self, timeout: Duration) -> Result<Vec<u8>, Error> {
+        if let Some(report) = self.queued.lock().unwrap().pop_front() {
+            return Ok(report);
+        }
+
+        self.read_report(Some(timeout))
+    }
+
+    /// Removes and returns every report queued by [`Device::execute`].
+    ///
+    /// These are reports the device sent on its own (e.g. `ReportMouseStatus`) or stale responses to
+    /// earlier commands that arrived while waiting for a response.
+    pub fn take_queued_reports(&self) -> Vec<Vec<u8>> {
+        self.queued.lock().unwrap().drain(..).collect()
+    }
+
+    fn read_report(&self, timeout: Option<Duration>) -> Result<Vec<u8>, Error> {
         let mut buf = [0u8; MAX_REPORT_LENGTH];
-        let bytes_read = self.0.read(&mut buf).map_err(|e| Error::HidError(e))?;
+        let bytes_read = self.transport.read_report(&mut buf, timeout)?;
+        // An empty read carries no report, not even the report ID
+        if bytes_read < 1 {
+            return Err(Error::Timeout);
+        }
 
         // Remove Report ID from the response
-        Ok(buf[1..bytes_read].to_vec())
+        Ok(buf[1..bytes_read.min(MAX_REPORT_LENGTH)].to_vec())
+    }
+
+    fn queue_report(&self, report: Vec<u8>) {
+        let mut queued = self.queued.lock().unwrap();
+        if queued.len() == MAX_QUEUED_REPORTS {
+            queued.pop_front();
+        }
+        queued.push_back(report);
     }
 
     /// Executes a command by sending it to the device and reading the response.
     ///
     /// This is a safe wrapper around the `send` and `read` as it ensures that the returned command type is same as the input command type.
     ///
+    /// Reports that are not a response to the command (see [`Command::is_answered_by`]) are queued and can be
+    /// retrieved later with [`Device::read`] or [`Device::take_queued_reports`]. Timeouts and corrupted
+    /// responses are retried according to the [`RetryPolicy`] of the device.
+    ///
+    /// When talking through a receiver, a failed command is followed by a `GetWirelessMouseOnline` query so
+    /// that a sleeping or disconnected mouse is reported as such rather than as a bogus response.
+    ///
     /// # Returns
     ///
     /// * `Ok(Command<T>)` if the command execution is successful.
-    /// * `Err(hidapi::HidError)` if the command execution fails.
+    /// * `Err(Error::MouseOffline)` if the device is a receiver and the mouse is not connected to it.
+    /// * `Err(Error::Timeout)` if the device did not answer in any of the attempts.
+    /// * `Err(Error::DeviceRejected)` if the device answered with a failure status.
+    /// * `Err(Error::NoMatchingResponse)` if the device kept sending unrelated reports.
+    /// * `Err(Error)` if the transport fails.
     ///
     /// # Examples
     /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// # #[derive(Command)]
+    /// # struct Query;
+    /// # let device = Device::new(0x1234, 0x5678, 0xFF00, 0x01).unwrap();
+    /// # let command = Command::<Query>::default();
     /// let response = device.execute(command).expect("Failed to execute command");
     /// println!("Response: {:?}", response);
     /// ```
-    pub fn execute<T: CommandDescriptor>(&self, command: Command<T>) -> Result<Command<T>, Error> {
-        self.send(&command)?;
-        let response = self.read()?;
+    pub fn execute<C: CommandDescriptor>(&self, command: Command<C>) -> Result<Command<C>, Error> {
+        let id = command.id();
+        let result = self.execute_with_retries(command);
+
+        let failed = matches!(
+            result,
+            Err(Error::DeviceRejected { .. }
+                | Error::ParseError(_)
+                | Error::Timeout
+                | Error::NoMatchingResponse(_))
+        );
+        // Only a receiver can tell whether the mouse is online, and its own commands do not
+        // depend on the mouse
+        if failed
+            && !id.is_receiver_command()
+            && self.connection() == ConnectionKind::Dongle
+            && matches!(self.is_mouse_online(), Ok(false))
+        {
+            return Err(Error::MouseOffline);
+        }
+
+        result
+    }
+
+    fn execute_with_retries<C: CommandDescriptor>(
+        &self,
+        command: Command<C>,
+    ) -> Result<Command<C>, Error> {
+        let policy = self.retry_policy;
+        let mut attempt = 1;
+        let mut resend = true;
+
+        loop {
+            if resend {
+                self.send(&command)?;
+            }
+
+            match self.receive(&command) {
+                Err(e @ (Error::Timeout | Error::ChecksumMismatch { .. }))
+                    if attempt < policy.attempts =>
+                {
+                    resend = policy.resend_on_timeout || !matches!(e, Error::Timeout);
+                    attempt += 1;
+                    std::thread::sleep(policy.backoff);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn receive<C: CommandDescriptor>(&self, command: &Command<C>) -> Result<Command<C>, Error> {
+        for _ in 0..MAX_SKIPPED_REPORTS {
+            let report = self.read_report(self.timeout)?;
+            if !command.is_answered_by(&report) {
+                self.queue_report(report);
+                continue;
+            }
+
+            let response = Command::try_from(report)?;
+            if !response.is_success() {
+                return Err(Error::DeviceRejected {
+                    status: response.status(),
+                });
+            }
+
+            return Ok(response);
+        }
+
+        Err(Error::NoMatchingResponse(command.id()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eeprom::Eeprom,
+        simulator::{Fault, Simulator},
+        types::{CommandId, EEPROMAddress},
+    };
+
+    fn device(policy: RetryPolicy) -> Device<Simulator<Eeprom>> {
+        let mut device = Device::from_transport(Simulator::new());
+        device.set_retry_policy(policy);
+        device
+    }
+
+    fn query() -> Command<Eeprom> {
+        Command::get(EEPROMAddress::CurrentDpi, 2).unwrap()
+    }
+
+    /// Number of `GetEEPROM` commands the simulator received
+    fn queries_sent(device: &Device<Simulator<Eeprom>>) -> usize {
+        device
+            .transport()
+            .received()
+            .iter()
+            .filter(|report| report[1] == CommandId::GetEEPROM as u8)
+            .count()
+    }
+
+    /// Number of `GetWirelessMouseOnline` commands the simulator received
+    fn online_checks_sent(device: &Device<Simulator<Eeprom>>) -> usize {
+        device
+            .transport()
+            .received()
+            .iter()
+            .filter(|report| report[1] == CommandId::GetWirelessMouseOnline as u8)
+            .count()
+    }
+
+    fn mouse_status() -> Vec<u8> {
+        let mut command = Command::<Eeprom>::default();
+        command.set_id(CommandId::ReportMouseStatus);
+        command.as_bytes()
+    }
+
+    /// A transport that never has anything to read
+    struct Silent;
+
+    impl Transport for Silent {
+        fn write_report(&self, data: &[u8]) -> Result<usize, Error> {
+            Ok(data.len())
+        }
+
+        fn read_report(&self, _buf: &mut [u8], _timeout: Option<Duration>) -> Result<usize, Error> {
+            Ok(0)
+        }
+
+        fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, Error> {
+            Ok(0)
+        }
+
+        fn device_info(&self) -> Result<DeviceInfo, Error> {
+            Ok(DeviceInfo::default())
+        }
+    }
+
+    #[test]
+    fn empty_reads_time_out() {
+        let mut device = Device::from_transport(Silent);
+        device.set_retry_policy(RetryPolicy::NONE);
+
+        assert!(matches!(device.read(), Err(Error::Timeout)));
+        assert!(matches!(device.execute(query()), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn unrelated_reports_are_queued() {
+        let device = device(RetryPolicy::NONE);
+        let stale = Command::<Eeprom>::get(EEPROMAddress::ReportRate, 2)
+            .unwrap()
+            .as_bytes();
+        device.transport().inject_report(&mouse_status());
+        device.transport().inject_report(&stale);
+
+        let response = device.execute(query()).unwrap();
+        assert_eq!(response.eeprom_address(), EEPROMAddress::CurrentDpi);
+
+        // `read` drains the queue before touching the transport
+        assert_eq!(device.read().unwrap(), mouse_status());
+        assert_eq!(device.take_queued_reports(), vec![stale]);
+        assert!(device.take_queued_reports().is_empty());
+        assert!(matches!(device.read(), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn too_many_unrelated_reports() {
+        let device = device(RetryPolicy::NONE);
+        for _ in 0..MAX_SKIPPED_REPORTS {
+            device.transport().inject_report(&mouse_status());
+        }
+
+        assert!(matches!(
+            device.execute(query()),
+            Err(Error::NoMatchingResponse(CommandId::GetEEPROM))
+        ));
+        let queued = device.take_queued_reports();
+        assert_eq!(
+            queued
+                .iter()
+                .filter(|&report| *report == mouse_status())
+                .count(),
+            MAX_SKIPPED_REPORTS
+        );
+    }
+
+    #[test]
+    fn rejected_commands_are_not_retried() {
+        let device = device(RetryPolicy::default());
+        device.transport().push_fault(Fault::Reject(0x2));
+
+        assert!(matches!(
+            device.execute(query()),
+            Err(Error::DeviceRejected { status: 0x2 })
+        ));
+        assert_eq!(queries_sent(&device), 1);
+    }
+
+    #[test]
+    fn bad_checksums_are_retried() {
+        let device = device(RetryPolicy::NONE);
+        device.transport().push_fault(Fault::BadChecksum);
+        assert!(matches!(
+            device.execute(query()),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        let device = self::device(RetryPolicy::default());
+        device.transport().push_fault(Fault::BadChecksum);
+        device.execute(query()).unwrap();
+        assert_eq!(queries_sent(&device), 2);
+    }
+
+    #[test]
+    fn timeouts_are_retried_with_backoff() {
+        let device = device(RetryPolicy::NONE);
+        device.transport().push_fault(Fault::DropReply);
+        assert!(matches!(device.execute(query()), Err(Error::Timeout)));
+
+        let backoff = Duration::from_millis(20);
+        let device = self::device(RetryPolicy {
+            attempts: 3,
+            backoff,
+            resend_on_timeout: true,
+        });
+        device.transport().push_fault(Fault::DropReply);
+        device.transport().push_fault(Fault::DropReply);
+
+        let start = std::time::Instant::now();
+        device.execute(query()).unwrap();
+        assert!(start.elapsed() >= backoff * 2);
+        assert_eq!(queries_sent(&device), 3);
+
+        device.transport().push_fault(Fault::DropReply);
+        device.transport().push_fault(Fault::DropReply);
+        device.transport().push_fault(Fault::DropReply);
+        assert!(matches!(device.execute(query()), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn late_responses_are_awaited_without_resending() {
+        let mut device = device(RetryPolicy {
+            attempts: 10,
+            backoff: Duration::ZERO,
+            resend_on_timeout: false,
+        });
+        device.set_timeout(Some(Duration::from_millis(10)));
+        device
+            .transport()
+            .push_fault(Fault::Delay(Duration::from_millis(35)));
+
+        device.execute(query()).unwrap();
+        assert_eq!(queries_sent(&device), 1);
+    }
+
+    #[test]
+    fn offline_mice_are_reported_through_a_receiver() {
+        let device = device(RetryPolicy::NONE);
+        device.transport().set_mouse_online(false);
+
+        assert!(matches!(device.execute(query()), Err(Error::MouseOffline)));
+        assert_eq!(online_checks_sent(&device), 1);
+    }
+
+    #[test]
+    fn only_receivers_are_asked_whether_the_mouse_is_online() {
+        for connection in [ConnectionKind::Wired, ConnectionKind::Unknown] {
+            let device = device(RetryPolicy::NONE);
+            device.transport().set_device_info(DeviceInfo {
+                connection,
+                ..Default::default()
+            });
+            device.transport().set_mouse_online(false);
 
-        Command::try_from(response)
+            assert!(matches!(
+                device.execute(query()),
+                Err(Error::DeviceRejected { .. })
+            ));
+            assert_eq!(online_checks_sent(&device), 0);
+        }
     }
 }
@@ -0,0 +1,344 @@
+use crate::{
+    backup::EepromImage,
+    buttons::MAX_BUTTONS,
+    device::Device,
+    macros::{MACRO_SLOT_LEN, MAX_MACROS},
+    shortcut::{MAX_SHORTCUTS, SHORTCUT_SLOT_LEN},
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+use std::ops::Range;
+
+/// Fields made of a value followed by its checksum, or of a group of values closed by a checksum
+static FIELDS: &[(EEPROMAddress, usize)] = &[
+    (EEPROMAddress::ReportRate, 2),
+    (EEPROMAddress::MaxDpi, 2),
+    (EEPROMAddress::CurrentDpi, 2),
+    (EEPROMAddress::SilentHeight, 2),
+    (EEPROMAddress::DpiPair1, 8),
+    (EEPROMAddress::DpiPair3, 8),
+    (EEPROMAddress::DpiPair5, 8),
+    (EEPROMAddress::DpiPair7, 8),
+    (EEPROMAddress::DpiPair1Color, 8),
+    (EEPROMAddress::DpiPair3Color, 8),
+    (EEPROMAddress::DpiPair5Color, 8),
+    (EEPROMAddress::DpiPair7Color, 8),
+    (EEPROMAddress::DpiRgbLightingEffects, 2),
+    (EEPROMAddress::DpiRgbLongBrightBrightness, 2),
+    (EEPROMAddress::DpiRgbLongBrightSpeed, 2),
+    (EEPROMAddress::DpiRgbEnable, 2),
+    (EEPROMAddress::ArticleLampR, 4),
+    (EEPROMAddress::ArticleLampEffects, 2),
+    (EEPROMAddress::ArticleLampLongBrightness, 2),
+    (EEPROMAddress::ArticleLampBreathingSpeed, 2),
+    (EEPROMAddress::ArticleLampEnergySaving, 2),
+    (EEPROMAddress::StabilizationTime, 2),
+    (EEPROMAddress::MotionSync, 2),
+    (EEPROMAddress::CloseLedTime, 2),
+    (EEPROMAddress::LinearCorrection, 2),
+    (EEPROMAddress::RippleControl, 2),
+    (EEPROMAddress::MoveCloseLights, 2),
+    (EEPROMAddress::SensorEnable, 2),
+    (EEPROMAddress::SensorTime, 2),
+    (EEPROMAddress::SensorMode, 2),
+    (EEPROMAddress::RfTxTime, 2),
+];
+
+/// A named region of the EEPROM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    pub address: EEPROMAddress,
+    pub len: usize,
+}
+
+impl Field {
+    /// Returns the name of the field.
+    pub fn name(&self) -> &'static str {
+        self.address.name().unwrap_or("Unknown")
+    }
+
+    /// Returns the byte range covered by the field.
+    pub fn range(&self) -> Range<usize> {
+        let start = self.address.value() as usize;
+        start..start + self.len
+    }
+}
+
+/// Returns every known field of the EEPROM, ordered by address.
+pub fn fields() -> Vec<Field> {
+    let slots = |first: EEPROMAddress, count: usize, len: usize| {
+        (0..count).map(move |index| Field {
+            address: EEPROMAddress::from(first.value() + (index * len) as u16),
+            len,
+        })
+    };
+
+    let mut fields: Vec<Field> = FIELDS
+        .iter()
+        .map(|&(address, len)| Field { address, len })
+        .chain(slots(EEPROMAddress::Key0, MAX_BUTTONS, 4))
+        .chain(slots(
+            EEPROMAddress::KeyShortcuts0,
+            MAX_SHORTCUTS,
+            SHORTCUT_SLOT_LEN,
+        ))
+        .chain(slots(EEPROMAddress::Macro0, MAX_MACROS, MACRO_SLOT_LEN))
+        .collect();
+    fields.sort_by_key(|field| field.address);
+
+    fields
+}
+
+/// A changed region of the EEPROM, either a whole field or a run of bytes outside any field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub address: EEPROMAddress,
+    pub old: Vec<u8>,
+    pub new: Vec<u8>,
+}
+
+impl FieldChange {
+    /// Returns the byte range covered by the change.
+    pub fn range(&self) -> Range<usize> {
+        let start = self.address.value() as usize;
+        start..start + self.new.len()
+    }
+
+    /// Returns the runs of bytes that actually differ, each widened to whole value / checksum pairs of
+    /// the field. A change outside any field is returned as a single run.
+    ///
+    /// These are the only ranges [`Device::apply_changes`] writes, so a one byte edit inside a macro
+    /// slot costs a two byte write rather than a rewrite of the whole slot.
+    pub fn changed_ranges(&self) -> Vec<Range<usize>> {
+        let start = self.range().start;
+        let pair_differs = |pair: usize| {
+            let pair = pair - start..(pair + 2 - start).min(self.new.len());
+            self.old[pair.clone()] != self.new[pair]
+        };
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for pair in (start..self.range().end)
+            .step_by(2)
+            .filter(|&pair| pair_differs(pair))
+        {
+            let end = (pair + 2).min(self.range().end);
+            match ranges.last_mut() {
+                Some(last) if last.end == pair => last.end = end,
+                _ => ranges.push(pair..end),
+            }
+        }
+
+        ranges
+    }
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.address.name() {
+            // A single value followed by its checksum
+            Some(name) if self.new.len() == 2 => {
+                write!(f, "{}: {} -> {}", name, self.old[0], self.new[0])
+            }
+            Some(name) => write!(f, "{} changed", name),
+            None => write!(
+                f,
+                "{:#06x}..{:#06x} changed",
+                self.range().start,
+                self.range().end
+            ),
+        }
+    }
+}
+
+impl EepromImage {
+    /// Lists the regions that differ between this image and `new`, ordered by address.
+    ///
+    /// A change inside a known field is reported for the whole field, other runs of changed bytes are
+    /// reported as they are. Only the bytes present in both images are compared.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::macros::{Macro, MacroEvent, RepeatMode};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let backup = device.dump_eeprom().unwrap();
+    ///
+    /// let mut m = Macro::new(RepeatMode::Once);
+    /// m.push(MacroEvent::KeyDown(0x04)).unwrap();
+    /// device.upload_macro(4, &m).unwrap();
+    /// device.set_report_rate(ReportRate::Hz125).unwrap();
+    ///
+    /// let changes = backup.diff(&device.dump_eeprom().unwrap());
+    /// let summary: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+    /// assert_eq!(summary, ["ReportRate: 0 -> 8", "Macro4 changed"]);
+    ///
+    /// // Only the report rate and the macro slot are written back
+    /// assert_eq!(device.apply_image(&backup, false).unwrap().len(), 2);
+    /// assert_eq!(device.dump_eeprom().unwrap().data, backup.data);
+    /// ```
+    pub fn diff(&self, new: &EepromImage) -> Vec<FieldChange> {
+        let len = self.data.len().min(new.data.len());
+        let fields = fields();
+        let field_at =
+            |address: usize| fields.iter().find(|field| field.range().contains(&address));
+
+        let mut changes = Vec::new();
+        let mut address = 0;
+        while address < len {
+            if self.data[address] == new.data[address] {
+                address += 1;
+                continue;
+            }
+
+            let range = match field_at(address) {
+                Some(field) => field.range().start..field.range().end.min(len),
+                None => {
+                    let mut end = address + 1;
+                    while end < len && self.data[end] != new.data[end] && field_at(end).is_none() {
+                        end += 1;
+                    }
+                    address..end
+                }
+            };
+
+            changes.push(FieldChange {
+                address: EEPROMAddress::from(range.start as u16),
+                old: self.data[range.clone()].to_vec(),
+                new: new.data[range.clone()].to_vec(),
+            });
+            address = range.end;
+        }
+
+        changes
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Writes the bytes that differ in each change, see [`FieldChange::changed_ranges`], verifying
+    /// every chunk.
+    pub fn apply_changes(&self, changes: &[FieldChange]) -> Result<(), Error> {
+        for change in changes {
+            let start = change.range().start;
+            for range in change.changed_ranges() {
+                self.write_eeprom(
+                    range.start as u16,
+                    &change.new[range.start - start..range.end - start],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Brings the EEPROM to the contents of `image`, only writing the bytes that differ.
+    ///
+    /// The current contents are read first to compute the changes.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(changes)` with the fields that were written to.
+    /// * `Err(Error::ModelMismatch)` if the image was taken from another model and `force` is false.
+    pub fn apply_image(&self, image: &EepromImage, force: bool) -> Result<Vec<FieldChange>, Error> {
+        self.check_image(image, force)?;
+
+        let changes = self.dump_eeprom()?.diff(image);
+        self.apply_changes(&changes)?;
+
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator, types::CommandId};
+
+    fn writes_sent(device: &Device<Simulator<Eeprom>>) -> Vec<(u16, usize)> {
+        device
+            .transport()
+            .received()
+            .iter()
+            .filter(|report| report[1] == CommandId::SetEEPROM as u8)
+            .map(|report| {
+                (
+                    u16::from_be_bytes([report[3], report[4]]),
+                    report[5] as usize,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_the_changed_pairs_are_written() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        let mut image = device.dump_eeprom().unwrap();
+        // One byte outside any field, two bytes far apart inside a macro slot
+        image.data[0x7] = 0x1;
+        image.data[0x385] = 0x2;
+        image.data[0x390] = 0x3;
+        image.data[0x391] = 0x4;
+        image.data[0x392] = 0x5;
+
+        let changes = device.dump_eeprom().unwrap().diff(&image);
+        let summary: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        assert_eq!(summary, ["0x0007..0x0008 changed", "Macro0 changed"]);
+        assert_eq!(changes[1].range(), 0x300..0x480);
+        assert_eq!(changes[0].changed_ranges(), [changes[0].range()]);
+        assert_eq!(changes[1].changed_ranges(), [0x384..0x386, 0x390..0x394]);
+
+        let written = writes_sent(&device).len();
+        device.apply_changes(&changes).unwrap();
+        assert_eq!(
+            writes_sent(&device)[written..],
+            [(0x7, 1), (0x384, 2), (0x390, 4)]
+        );
+        assert_eq!(device.transport().eeprom()[..0x480], image.data[..0x480]);
+    }
+
+    #[test]
+    fn unnamed_bytes_next_to_odd_fields_are_kept_apart() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        let mut image = device.dump_eeprom().unwrap();
+        // Around the block from `StabilizationTime` (0xa9) to `RfTxTimeCRC` (0xbc)
+        image.data[0xa8] = 0x1;
+        image.data[0xa9] = 0x2;
+        image.data[0xbb] = 0x4;
+        image.data[0xbd] = 0x5;
+
+        let changes = device.dump_eeprom().unwrap().diff(&image);
+        let ranges: Vec<Range<usize>> = changes.iter().map(FieldChange::range).collect();
+        assert_eq!(ranges, [0xa8..0xa9, 0xa9..0xab, 0xbb..0xbd, 0xbd..0xbe]);
+        let summary: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        assert_eq!(
+            summary,
+            [
+                "0x00a8..0x00a9 changed",
+                "StabilizationTime: 0 -> 2",
+                "RfTxTime: 0 -> 4",
+                "0x00bd..0x00be changed"
+            ]
+        );
+
+        let written = writes_sent(&device).len();
+        device.apply_changes(&changes).unwrap();
+        assert_eq!(
+            writes_sent(&device)[written..],
+            [(0xa8, 1), (0xa9, 2), (0xbb, 2), (0xbd, 1)]
+        );
+        assert_eq!(
+            device.transport().eeprom()[0xa8..0xbe],
+            image.data[0xa8..0xbe]
+        );
+    }
+
+    #[test]
+    fn unchanged_images_write_nothing() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        let image = device.dump_eeprom().unwrap();
+
+        assert!(device.apply_image(&image, false).unwrap().is_empty());
+        assert!(writes_sent(&device).is_empty());
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{EEPROMAddress, Error, Rgb},
+};
+
+/*
+DPI stages are stored pairwise, two stages per `DpiPairN` address and two colors per `DpiPairNColor`
+address. `MaxDpi` holds the number of enabled stages and `CurrentDpi` the index of the active one.
+
+Each stage takes 4 bytes, DPI values are stored in steps of 50 as `dpi / 50 - 1` on 10 bits:
+┌────────────┬────────────┬──────────────────────────────────────┬──────────┐
+│  X [7:0]   │  Y [7:0]   │ X [9:8] in bits 2-3, Y [9:8] in 6-7  │ Checksum │
+└────────────┴────────────┴──────────────────────────────────────┴──────────┘
+
+Each color takes 4 bytes:
+┌─────┬─────┬─────┬──────────┐
+│  R  │  G  │  B  │ Checksum │
+└─────┴─────┴─────┴──────────┘
+*/
+
+/// Maximum number of DPI stages stored in the EEPROM
+pub const MAX_DPI_STAGES: usize = 8;
+/// Granularity of the DPI values
+pub const DPI_STEP: u32 = 50;
+/// Largest DPI value that can be encoded
+pub const MAX_ENCODABLE_DPI: u32 = 0x400 * DPI_STEP;
+
+static STAGE_PAIRS: [EEPROMAddress; 4] = [
+    EEPROMAddress::DpiPair1,
+    EEPROMAddress::DpiPair3,
+    EEPROMAddress::DpiPair5,
+    EEPROMAddress::DpiPair7,
+];
+static COLOR_PAIRS: [EEPROMAddress; 4] = [
+    EEPROMAddress::DpiPair1Color,
+    EEPROMAddress::DpiPair3Color,
+    EEPROMAddress::DpiPair5Color,
+    EEPROMAddress::DpiPair7Color,
+];
+
+/// A single DPI stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DpiStage {
+    pub x: u32,
+    pub y: u32,
+    /// Color of the DPI indicator while the stage is active
+    pub color: Rgb,
+}
+
+impl DpiStage {
+    /// Creates a stage using the same DPI on both axes.
+    pub fn new(dpi: u32, color: Rgb) -> Self {
+        Self {
+            x: dpi,
+            y: dpi,
+            color,
+        }
+    }
+}
+
+/// The DPI stages of the mouse along with the active one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DpiStages {
+    /// Index of the active stage in `stages`
+    pub active: usize,
+    pub stages: Vec<DpiStage>,
+}
+
+impl DpiStages {
+    /// Returns the number of enabled stages.
+    pub fn count(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns the active stage.
+    pub fn active_stage(&self) -> Option<&DpiStage> {
+        self.stages.get(self.active)
+    }
+}
+
+fn encode_dpi(dpi: u32) -> Result<u16, Error> {
+    if !(DPI_STEP..=MAX_ENCODABLE_DPI).contains(&dpi) || dpi % DPI_STEP != 0 {
+        return Err(Error::InvalidSetting(format!(
+            "DPI must be a multiple of {} between {} and {}: {}",
+            DPI_STEP, DPI_STEP, MAX_ENCODABLE_DPI, dpi
+        )));
+    }
+
+    Ok((dpi / DPI_STEP - 1) as u16)
+}
+
+fn decode_dpi(raw: u16) -> u32 {
+    (raw as u32 + 1) * DPI_STEP
+}
+
+fn encode_stage(stage: &DpiStage) -> Result<Vec<u8>, Error> {
+    let x = encode_dpi(stage.x)?;
+    let y = encode_dpi(stage.y)?;
+    let high = (((x >> 8) & 0x3) << 2 | ((y >> 8) & 0x3) << 6) as u8;
+
+    Ok(with_checksum(&[x as u8, y as u8, high]))
+}
+
+fn decode_stage(group: &[u8], color: &[u8]) -> Result<DpiStage, Error> {
+    let values = verify_checksum(group)?;
+    let x = values[0] as u16 | ((values[2] as u16 >> 2) & 0x3) << 8;
+    let y = values[1] as u16 | ((values[2] as u16 >> 6) & 0x3) << 8;
+    let color = verify_checksum(color)?;
+
+    Ok(DpiStage {
+        x: decode_dpi(x),
+        y: decode_dpi(y),
+        color: Rgb::new(color[0], color[1], color[2]),
+    })
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the DPI stages, verifying every checksum.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// let device = Device::open_any().expect("No supported device found");
+    /// let stages = device.get_dpi_stages().expect("Failed to read DPI stages");
+    /// for (i, stage) in stages.stages.iter().enumerate() {
+    ///     println!("{}: {}x{} {:?}", i, stage.x, stage.y, stage.color);
+    /// }
+    /// ```
+    pub fn get_dpi_stages(&self) -> Result<DpiStages, Error> {
+        let count = self.get_checked_value(EEPROMAddress::MaxDpi)? as usize;
+        if count == 0 || count > MAX_DPI_STAGES {
+            return Err(Error::ParseError(format!(
+                "Invalid number of DPI stages: {}",
+                count
+            )));
+        }
+        let active = self.get_checked_value(EEPROMAddress::CurrentDpi)? as usize;
+        if active >= count {
+            return Err(Error::ParseError(format!(
+                "Invalid active DPI stage: {}",
+                active
+            )));
+        }
+
+        let mut stages = Vec::with_capacity(count);
+        for (&stage_pair, &color_pair) in STAGE_PAIRS.iter().zip(&COLOR_PAIRS) {
+            if stages.len() == count {
+                break;
+            }
+            let len = if count - stages.len() > 1 { 8 } else { 4 };
+            let raw_stages = self.get_eeprom(stage_pair, len)?;
+            let raw_colors = self.get_eeprom(color_pair, len)?;
+            for (stage, color) in raw_stages.chunks(4).zip(raw_colors.chunks(4)) {
+                stages.push(decode_stage(stage, color)?);
+            }
+        }
+
+        Ok(DpiStages { active, stages })
+    }
+
+    /// Writes the DPI stages along with their checksums.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the stages were written.
+    /// * `Err(Error::InvalidSetting)` if the number of stages, the active stage or a DPI value is out of range
+    ///   for the encoding or for the model of the device.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::dpi::{DpiStage, DpiStages};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let stages = DpiStages {
+    ///     active: 1,
+    ///     stages: vec![
+    ///         DpiStage::new(400, Rgb::new(0xff, 0, 0)),
+    ///         DpiStage::new(800, Rgb::new(0, 0xff, 0)),
+    ///         DpiStage { x: 1600, y: 1200, color: Rgb::new(0, 0, 0xff) },
+    ///     ],
+    /// };
+    /// device.set_dpi_stages(&stages).unwrap();
+    /// assert_eq!(device.get_dpi_stages().unwrap(), stages);
+    /// ```
+    pub fn set_dpi_stages(&self, stages: &DpiStages) -> Result<(), Error> {
+        let max_stages = self
+            .model()
+            .map_or(MAX_DPI_STAGES, |model| model.dpi_stages as usize);
+        if stages.count() == 0 || stages.count() > max_stages {
+            return Err(Error::InvalidSetting(format!(
+                "Number of DPI stages must be between 1 and {}: {}",
+                max_stages,
+                stages.count()
+            )));
+        }
+        if stages.active >= stages.count() {
+            return Err(Error::InvalidSetting(format!(
+                "Active DPI stage out of range: {}",
+                stages.active
+            )));
+        }
+        if let Some(model) = self.model() {
+            if let Some(stage) = stages
+                .stages
+                .iter()
+                .find(|stage| stage.x.max(stage.y) > model.max_dpi)
+            {
+                return Err(Error::InvalidSetting(format!(
+                    "{} supports up to {} DPI: {}x{}",
+                    model.name, model.max_dpi, stage.x, stage.y
+                )));
+            }
+        }
+
+        for (i, pair) in stages.stages.chunks(2).enumerate() {
+            let mut raw_stages = Vec::with_capacity(8);
+            let mut raw_colors = Vec::with_capacity(8);
+            for stage in pair {
+                raw_stages.extend(encode_stage(stage)?);
+                raw_colors.extend(with_checksum(&[
+                    stage.color.r,
+                    stage.color.g,
+                    stage.color.b,
+                ]));
+            }
+            self.set_eeprom(STAGE_PAIRS[i], &raw_stages)?;
+            self.set_eeprom(COLOR_PAIRS[i], &raw_colors)?;
+        }
+
+        self.set_checked_value(EEPROMAddress::MaxDpi, stages.count() as u8)?;
+        self.set_checked_value(EEPROMAddress::CurrentDpi, stages.active as u8)
+    }
+
+    /// Switches to another DPI stage.
+    pub fn set_active_dpi_stage(&self, index: usize) -> Result<(), Error> {
+        let count = self.get_checked_value(EEPROMAddress::MaxDpi)? as usize;
+        if index >= count {
+            return Err(Error::InvalidSetting(format!(
+                "Active DPI stage out of range: {}",
+                index
+            )));
+        }
+
+        self.set_checked_value(EEPROMAddress::CurrentDpi, index as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator};
+
+    /// `MaxDpi` and `CurrentDpi`
+    const COUNTS: [u8; 4] = [0x3, 0x52, 0x1, 0x54];
+    /// 400x400, 26000x12850 and 1600x1200 from `DpiPair1`
+    const STAGES: [u8; 12] = [
+        0x07, 0x07, 0x00, 0x47, //
+        0x07, 0x00, 0x48, 0x06, //
+        0x1f, 0x17, 0x00, 0x1f,
+    ];
+    /// Red, green and blue from `DpiPair1Color`
+    const COLORS: [u8; 12] = [
+        0xff, 0x00, 0x00, 0x56, //
+        0x00, 0xff, 0x00, 0x56, //
+        0x00, 0x00, 0xff, 0x56,
+    ];
+
+    fn stages() -> DpiStages {
+        DpiStages {
+            active: 1,
+            stages: vec![
+                DpiStage::new(400, Rgb::new(0xff, 0, 0)),
+                DpiStage {
+                    x: 26000,
+                    y: 12850,
+                    color: Rgb::new(0, 0xff, 0),
+                },
+                DpiStage {
+                    x: 1600,
+                    y: 1200,
+                    color: Rgb::new(0, 0, 0xff),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn writes_the_atk_layout() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_dpi_stages(&stages()).unwrap();
+
+        let eeprom = device.transport().eeprom();
+        assert_eq!(eeprom[0x2..0x6], COUNTS);
+        assert_eq!(eeprom[0xc..0x18], STAGES);
+        // The unused half of the last pair is left alone
+        assert_eq!(eeprom[0x18..0x2c], [0x0; 0x14]);
+        assert_eq!(eeprom[0x2c..0x38], COLORS);
+    }
+
+    #[test]
+    fn reads_the_atk_layout() {
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.write_eeprom(0x2, &COUNTS);
+        simulator.write_eeprom(0xc, &STAGES);
+        simulator.write_eeprom(0x2c, &COLORS);
+        let device = Device::from_transport(simulator);
+
+        assert_eq!(device.get_dpi_stages().unwrap(), stages());
+    }
+
+    #[test]
+    fn rejects_values_outside_the_encoding() {
+        assert_eq!(encode_dpi(50).unwrap(), 0x0);
+        assert_eq!(encode_dpi(MAX_ENCODABLE_DPI).unwrap(), 0x3ff);
+        assert!(encode_dpi(0).is_err());
+        assert!(encode_dpi(425).is_err());
+        assert!(encode_dpi(MAX_ENCODABLE_DPI + DPI_STEP).is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{
+    command::{Command, CommandDescriptor},
+    device::Device,
+    transport::Transport,
+    types::{CommandId, EEPROMAddress, Error},
+};
+use libatk_derive::Command;
+
+/// Size of the EEPROM, covering every known address
+pub const EEPROM_SIZE: usize = 0x1b00;
+
+/// Descriptor of the `GetEEPROM` / `SetEEPROM` commands issued by the typed configuration APIs.
+#[derive(Command)]
+pub struct Eeprom;
+
+impl Command<Eeprom> {
+    /// Builds a command reading `len` bytes starting at `address`.
+    pub fn get(address: EEPROMAddress, len: usize) -> Result<Self, Error> {
+        let mut command = Command::default();
+        command.set_id(CommandId::GetEEPROM);
+        command.set_eeprom_address(address);
+        command.set_data_len(len)?;
+
+        Ok(command)
+    }
+
+    /// Builds a command writing `data` starting at `address`.
+    pub fn set(address: EEPROMAddress, data: &[u8]) -> Result<Self, Error> {
+        let mut command = Command::default();
+        command.set_id(CommandId::SetEEPROM);
+        command.set_eeprom_address(address);
+        command.set_data_len(data.len())?;
+        command.set_data(data, 0)?;
+
+        Ok(command)
+    }
+}
+
+/// Appends the checksum of a group of EEPROM value bytes.
+pub(crate) fn with_checksum(bytes: &[u8]) -> Vec<u8> {
+    [bytes, &[Eeprom::CHECKSUM.value(bytes)]].concat()
+}
+
+/// Verifies the checksum closing a group of EEPROM value bytes and returns the values.
+pub(crate) fn verify_checksum(group: &[u8]) -> Result<&[u8], Error> {
+    let (values, checksum) = group.split_at(group.len() - 1);
+    let expected = Eeprom::CHECKSUM.value(values);
+    if checksum[0] != expected {
+        return Err(Error::ChecksumMismatch {
+            expected,
+            actual: checksum[0],
+        });
+    }
+
+    Ok(values)
+}
+
+/// Checks that every byte of `len` bytes starting at `address` is addressable.
+fn check_range(address: u16, len: usize) -> Result<(), Error> {
+    let Some(last) = len.checked_sub(1) else {
+        return Ok(());
+    };
+
+    u16::try_from(last)
+        .ok()
+        .and_then(|last| address.checked_add(last))
+        .map(|_| ())
+        .ok_or(Error::InvalidOffset((address as usize).saturating_add(len)))
+}
+
+/// Returns the address `offset` bytes past `address`, the range must have been checked beforehand.
+fn chunk_address(address: u16, offset: usize) -> EEPROMAddress {
+    EEPROMAddress::from(address + offset as u16)
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads `len` bytes starting at `address` with a single command.
+    pub(crate) fn get_eeprom(&self, address: EEPROMAddress, len: usize) -> Result<Vec<u8>, Error> {
+        let response = self.execute(Command::<Eeprom>::get(address, len)?)?;
+        if response.data_len() != len {
+            return Err(Error::InvalidBufferLength {
+                expected: len,
+                actual: response.data_len(),
+            });
+        }
+
+        Ok(response.data()[..len].to_vec())
+    }
+
+    /// Writes `data` starting at `address` with a single command.
+    pub(crate) fn set_eeprom(&self, address: EEPROMAddress, data: &[u8]) -> Result<(), Error> {
+        self.execute(Command::<Eeprom>::set(address, data)?)?;
+        Ok(())
+    }
+
+    /// Reads `len` bytes starting at `address`, split into as many `GetEEPROM` commands as needed.
+    ///
+    /// Each reply must carry the requested address and length.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<u8>)` with the bytes read.
+    /// * `Err(Error::InvalidOffset)` if the range runs past the last address, before anything is sent.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let address = EEPROMAddress::Key0.value();
+    /// device.write_eeprom(address, &[0x5a; 0x40]).unwrap();
+    ///
+    /// let mut chunks = 0;
+    /// let keys = device
+    ///     .read_eeprom_with_progress(address, 0x40, |done, total| {
+    ///         chunks += 1;
+    ///         assert!(done <= total);
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(keys, [0x5a; 0x40]);
+    /// assert_eq!(chunks, 7);
+    /// ```
+    pub fn read_eeprom(&self, address: u16, len: usize) -> Result<Vec<u8>, Error> {
+        self.read_eeprom_with_progress(address, len, |_, _| {})
+    }
+
+    /// Same as [`Device::read_eeprom`], calling `progress` with the number of bytes read so far and the total
+    /// after each chunk.
+    pub fn read_eeprom_with_progress(
+        &self,
+        address: u16,
+        len: usize,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<u8>, Error> {
+        check_range(address, len)?;
+
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let chunk_len = (len - data.len()).min(Eeprom::MAX_DATA_LEN);
+            data.extend(self.get_eeprom(chunk_address(address, data.len()), chunk_len)?);
+            progress(data.len(), len);
+        }
+
+        Ok(data)
+    }
+
+    /// Writes `data` starting at `address`, split into as many `SetEEPROM` commands as needed.
+    ///
+    /// Each chunk is read back after it is written.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if every chunk was written and verified.
+    /// * `Err(Error::InvalidOffset)` if the range runs past the last address, before anything is sent.
+    /// * `Err(Error::VerificationFailed)` if a chunk read back differs from what was written.
+    pub fn write_eeprom(&self, address: u16, data: &[u8]) -> Result<(), Error> {
+        self.write_eeprom_with_progress(address, data, |_, _| {})
+    }
+
+    /// Same as [`Device::write_eeprom`], calling `progress` with the number of bytes written so far and the
+    /// total after each chunk.
+    pub fn write_eeprom_with_progress(
+        &self,
+        address: u16,
+        data: &[u8],
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<(), Error> {
+        check_range(address, data.len())?;
+
+        let mut written = 0;
+        for chunk in data.chunks(Eeprom::MAX_DATA_LEN) {
+            let chunk_address = chunk_address(address, written);
+            self.set_eeprom(chunk_address, chunk)?;
+            if self.get_eeprom(chunk_address, chunk.len())? != chunk {
+                return Err(Error::VerificationFailed {
+                    address: chunk_address.value(),
+                });
+            }
+
+            written += chunk.len();
+            progress(written, data.len());
+        }
+
+        Ok(())
+    }
+
+    /// Reads a value stored with its checksum byte, e.g. `ReportRate` / `ReportRateCrc`.
+    pub(crate) fn get_checked_value(&self, address: EEPROMAddress) -> Result<u8, Error> {
+        Ok(verify_checksum(&self.get_eeprom(address, 2)?)?[0])
+    }
+
+    /// Writes a value along with its checksum byte.
+    pub(crate) fn set_checked_value(&self, address: EEPROMAddress, value: u8) -> Result<(), Error> {
+        self.set_eeprom(address, &with_checksum(&[value]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::{Fault, Simulator};
+    use std::time::Duration;
+
+    #[test]
+    fn ranges_past_the_last_address_are_rejected() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+
+        assert!(matches!(
+            device.read_eeprom(0xfff8, 0x10),
+            Err(Error::InvalidOffset(0x10008))
+        ));
+        assert!(matches!(
+            device.write_eeprom(0xfff8, &[0x0; 0x10]),
+            Err(Error::InvalidOffset(0x10008))
+        ));
+        assert!(device.read_eeprom(0x0, 0x10001).is_err());
+        assert!(device.read_eeprom(0x1, usize::MAX).is_err());
+        assert!(device.transport().received().is_empty());
+
+        // The last address itself can be reached
+        assert!(check_range(0xfff0, 0x10).is_ok());
+        assert!(device.read_eeprom(0xffff, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn chunks_are_verified_after_writing() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        let data: Vec<u8> = (0..0x18).collect();
+
+        // The first chunk goes through, the second one is acknowledged with a success status but never
+        // applied
+        device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        device.transport().push_fault(Fault::Reject(0x0));
+        assert!(matches!(
+            device.write_eeprom(0x100, &data),
+            Err(Error::VerificationFailed { address: 0x10a })
+        ));
+        assert_eq!(device.transport().eeprom()[0x100..0x10a], data[..0xa]);
+    }
+}
@@ -1,10 +1,32 @@
+pub mod backup;
+pub mod battery;
+pub mod buttons;
+pub mod checksum;
 pub mod command;
 pub mod device;
+pub mod diff;
+pub mod dpi;
+pub mod eeprom;
+pub mod lighting;
+pub mod macros;
+pub mod model;
+pub mod power;
+pub mod profile;
+pub mod report_rate;
+pub mod sensor;
+pub mod shortcut;
+pub mod simulator;
+pub mod transport;
 pub mod types;
+pub mod wireless;
 
 pub mod prelude {
+    pub use crate::checksum::{Checksum, SubtractChecksum};
     pub use crate::command::{Command, CommandBuilder, CommandDescriptor};
-    pub use crate::device::Device;
-    pub use crate::types::{CommandId, EEPROMAddress, Error};
+    pub use crate::device::{Device, RetryPolicy};
+    pub use crate::model::DeviceModel;
+    pub use crate::report_rate::ReportRate;
+    pub use crate::transport::{ConnectionKind, DeviceInfo, HidTransport, Transport};
+    pub use crate::types::{CommandId, EEPROMAddress, Error, Rgb};
     pub use libatk_derive::{command_extension, Command};
 }
@@ -0,0 +1,304 @@
+use crate::{
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{check_range, EEPROMAddress, Error, Rgb},
+};
+use std::ops::RangeInclusive;
+
+/// Brightness levels accepted by the lighting effects
+pub const BRIGHTNESS_RANGE: RangeInclusive<u8> = 1..=10;
+/// Speed levels accepted by the animated lighting effects
+pub const SPEED_RANGE: RangeInclusive<u8> = 1..=5;
+
+/// Effect of the DPI indicator.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DpiLightingEffect {
+    Off = 0x0,
+    /// Always on with the color of the active DPI stage
+    Steady = 0x1,
+    Breathing = 0x2,
+}
+
+impl TryFrom<u8> for DpiLightingEffect {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x0 => Ok(DpiLightingEffect::Off),
+            0x1 => Ok(DpiLightingEffect::Steady),
+            0x2 => Ok(DpiLightingEffect::Breathing),
+            _ => Err(Error::ParseError(format!(
+                "Invalid DPI lighting effect: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Lighting settings of the DPI indicator, stored from `DpiRgbLightingEffects` to `DpiRgbEnableCrc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DpiLighting {
+    pub effect: DpiLightingEffect,
+    /// Brightness in [`BRIGHTNESS_RANGE`]
+    pub brightness: u8,
+    /// Speed in [`SPEED_RANGE`]
+    pub speed: u8,
+    pub enabled: bool,
+}
+
+/// Effect of the logo LED.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ArticleLampEffect {
+    Off = 0x0,
+    /// Always on with the configured color
+    Steady = 0x1,
+    Breathing = 0x2,
+    /// Cycles through the colors, ignoring the configured one
+    Spectrum = 0x3,
+}
+
+impl TryFrom<u8> for ArticleLampEffect {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x0 => Ok(ArticleLampEffect::Off),
+            0x1 => Ok(ArticleLampEffect::Steady),
+            0x2 => Ok(ArticleLampEffect::Breathing),
+            0x3 => Ok(ArticleLampEffect::Spectrum),
+            _ => Err(Error::ParseError(format!(
+                "Invalid article lamp effect: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Settings of the logo LED (the "article lamp"), stored from `ArticleLampR` to `ArticleLampEnergySavingCRC`.
+///
+/// The three color channels share a single checksum byte, `ArticleLampCRC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArticleLamp {
+    pub color: Rgb,
+    pub effect: ArticleLampEffect,
+    /// Brightness in [`BRIGHTNESS_RANGE`]
+    pub brightness: u8,
+    /// Breathing speed in [`SPEED_RANGE`]
+    pub breathing_speed: u8,
+    /// Turns the LED off while the mouse is idle
+    pub energy_saving: bool,
+}
+
+/// Rejects lighting writes on models without any RGB zone.
+pub(crate) fn check_rgb_support<T: Transport>(device: &Device<T>) -> Result<(), Error> {
+    match device.model() {
+        Some(model) if model.rgb_zones == 0 => Err(Error::InvalidSetting(format!(
+            "{} has no RGB lighting",
+            model.name
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the DPI indicator lighting, verifying every checksum.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::lighting::{DpiLighting, DpiLightingEffect};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let lighting = DpiLighting {
+    ///     effect: DpiLightingEffect::Breathing,
+    ///     brightness: 8,
+    ///     speed: 3,
+    ///     enabled: true,
+    /// };
+    /// device.set_dpi_lighting(&lighting).unwrap();
+    /// assert_eq!(device.get_dpi_lighting().unwrap(), lighting);
+    /// ```
+    pub fn get_dpi_lighting(&self) -> Result<DpiLighting, Error> {
+        let raw = self.get_eeprom(EEPROMAddress::DpiRgbLightingEffects, 8)?;
+        let mut values = raw
+            .chunks(2)
+            .map(|pair| verify_checksum(pair).map(|v| v[0]));
+
+        Ok(DpiLighting {
+            effect: values.next().unwrap()?.try_into()?,
+            brightness: values.next().unwrap()?,
+            speed: values.next().unwrap()?,
+            enabled: values.next().unwrap()? != 0,
+        })
+    }
+
+    /// Writes the DPI indicator lighting along with its checksums in a single command.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the settings were written.
+    /// * `Err(Error::InvalidSetting)` if the brightness or speed is out of range, or the model has no RGB lighting.
+    pub fn set_dpi_lighting(&self, lighting: &DpiLighting) -> Result<(), Error> {
+        check_rgb_support(self)?;
+        check_range("Brightness", lighting.brightness, &BRIGHTNESS_RANGE)?;
+        check_range("Speed", lighting.speed, &SPEED_RANGE)?;
+
+        let raw: Vec<u8> = [
+            lighting.effect as u8,
+            lighting.brightness,
+            lighting.speed,
+            lighting.enabled as u8,
+        ]
+        .iter()
+        .flat_map(|&value| with_checksum(&[value]))
+        .collect();
+
+        self.set_eeprom(EEPROMAddress::DpiRgbLightingEffects, &raw)
+    }
+
+    /// Reads the logo LED settings, verifying every checksum.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::lighting::{ArticleLamp, ArticleLampEffect};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let lamp = ArticleLamp {
+    ///     color: Rgb::new(0x80, 0x00, 0xff),
+    ///     effect: ArticleLampEffect::Steady,
+    ///     brightness: 10,
+    ///     breathing_speed: 1,
+    ///     energy_saving: true,
+    /// };
+    /// device.set_article_lamp(&lamp).unwrap();
+    /// assert_eq!(device.get_article_lamp().unwrap(), lamp);
+    /// ```
+    pub fn get_article_lamp(&self) -> Result<ArticleLamp, Error> {
+        let mut raw = self.get_eeprom(EEPROMAddress::ArticleLampR, 8)?;
+        raw.extend(self.get_eeprom(EEPROMAddress::ArticleLampBreathingSpeed, 4)?);
+
+        let (color, settings) = raw.split_at(4);
+        let color = verify_checksum(color)?;
+        let values = settings
+            .chunks(2)
+            .map(|pair| verify_checksum(pair).map(|value| value[0]))
+            .collect::<Result<Vec<u8>, Error>>()?;
+
+        Ok(ArticleLamp {
+            color: Rgb::new(color[0], color[1], color[2]),
+            effect: values[0].try_into()?,
+            brightness: values[1],
+            breathing_speed: values[2],
+            energy_saving: values[3] != 0,
+        })
+    }
+
+    /// Writes the logo LED settings along with their checksums.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the settings were written.
+    /// * `Err(Error::InvalidSetting)` if the brightness or breathing speed is out of range, or the model has no
+    ///   RGB lighting.
+    pub fn set_article_lamp(&self, lamp: &ArticleLamp) -> Result<(), Error> {
+        check_rgb_support(self)?;
+        check_range("Brightness", lamp.brightness, &BRIGHTNESS_RANGE)?;
+        check_range("Breathing speed", lamp.breathing_speed, &SPEED_RANGE)?;
+
+        let mut raw = with_checksum(&[lamp.color.r, lamp.color.g, lamp.color.b]);
+        for value in [
+            lamp.effect as u8,
+            lamp.brightness,
+            lamp.breathing_speed,
+            lamp.energy_saving as u8,
+        ] {
+            raw.extend(with_checksum(&[value]));
+        }
+
+        self.set_eeprom(EEPROMAddress::ArticleLampR, &raw[..8])?;
+        self.set_eeprom(EEPROMAddress::ArticleLampBreathingSpeed, &raw[8..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator};
+
+    /// Breathing, brightness 8, speed 3, enabled from `DpiRgbLightingEffects`
+    const DPI_LIGHTING: [u8; 8] = [0x02, 0x53, 0x08, 0x4d, 0x03, 0x52, 0x01, 0x54];
+
+    fn dpi_lighting() -> DpiLighting {
+        DpiLighting {
+            effect: DpiLightingEffect::Breathing,
+            brightness: 8,
+            speed: 3,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn dpi_lighting_uses_the_atk_layout() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_dpi_lighting(&dpi_lighting()).unwrap();
+        assert_eq!(device.transport().eeprom()[0x4c..0x54], DPI_LIGHTING);
+
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.write_eeprom(0x4c, &DPI_LIGHTING);
+        let device = Device::from_transport(simulator);
+        assert_eq!(device.get_dpi_lighting().unwrap(), dpi_lighting());
+    }
+
+    #[test]
+    fn article_lamp_uses_the_atk_layout() {
+        // The color channels share one checksum, the other settings have their own
+        let raw = [
+            0x80, 0x00, 0xff, 0xd6, 0x01, 0x54, 0x0a, 0x4b, 0x01, 0x54, 0x01, 0x54,
+        ];
+        let lamp = ArticleLamp {
+            color: Rgb::new(0x80, 0x00, 0xff),
+            effect: ArticleLampEffect::Steady,
+            brightness: 10,
+            breathing_speed: 1,
+            energy_saving: true,
+        };
+
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_article_lamp(&lamp).unwrap();
+        assert_eq!(device.transport().eeprom()[0x54..0x60], raw);
+
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.write_eeprom(0x54, &raw);
+        let device = Device::from_transport(simulator);
+        assert_eq!(device.get_article_lamp().unwrap(), lamp);
+    }
+
+    #[test]
+    fn dpi_lighting_is_validated() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        for lighting in [
+            DpiLighting {
+                brightness: 0,
+                ..dpi_lighting()
+            },
+            DpiLighting {
+                speed: 6,
+                ..dpi_lighting()
+            },
+        ] {
+            assert!(matches!(
+                device.set_dpi_lighting(&lighting),
+                Err(Error::InvalidSetting(_))
+            ));
+        }
+        assert_eq!(device.transport().eeprom()[0x4c..0x54], [0x0; 8]);
+    }
+}
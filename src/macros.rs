@@ -0,0 +1,410 @@
+use crate::{
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+use std::time::Duration;
+
+/*
+Each macro slot, `Macro0` to `Macro15`, is 0x180 bytes long and starts with a header:
+┌─────────────┬──────────────┬─────────────┬────────────────────┬──────────┐
+│ Repeat Mode │ Repeat Count │ Event Count │       Events       │ Checksum │
+│   1 Byte    │    1 Byte    │ 2 Bytes BE  │ 3 Bytes per event  │  1 Byte  │
+└─────────────┴──────────────┴─────────────┴────────────────────┴──────────┘
+The checksum covers the header and the events, the rest of the slot is unused.
+
+┌──────┬──────────────┬─────────────┬──────────────────┐
+│ Kind │ Event        │ Byte 1      │ Byte 2           │
+├──────┼──────────────┼─────────────┼──────────────────┤
+│ 0x01 │ Key down     │ 0           │ HID usage code   │
+│ 0x02 │ Key up       │ 0           │ HID usage code   │
+│ 0x03 │ Button down  │ 0           │ Button mask      │
+│ 0x04 │ Button up    │ 0           │ Button mask      │
+│ 0x05 │ Delay        │ ms [15:8]   │ ms [7:0]         │
+└──────┴──────────────┴─────────────┴──────────────────┘
+*/
+
+/// Size of a macro slot in the EEPROM
+pub const MACRO_SLOT_LEN: usize = 0x180;
+/// Number of macro slots
+pub const MAX_MACROS: usize = 16;
+/// Size of the header preceding the events
+pub const MACRO_HEADER_LEN: usize = 4;
+/// Size of a single encoded event
+pub const MACRO_EVENT_LEN: usize = 3;
+/// Maximum number of events fitting in a slot
+pub const MAX_MACRO_EVENTS: usize = (MACRO_SLOT_LEN - MACRO_HEADER_LEN - 1) / MACRO_EVENT_LEN;
+
+/// A mouse button pressed or released by a macro.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MouseButton {
+    Left = 0x1,
+    Right = 0x2,
+    Middle = 0x4,
+    Back = 0x8,
+    Forward = 0x10,
+}
+
+impl TryFrom<u8> for MouseButton {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x1 => Ok(MouseButton::Left),
+            0x2 => Ok(MouseButton::Right),
+            0x4 => Ok(MouseButton::Middle),
+            0x8 => Ok(MouseButton::Back),
+            0x10 => Ok(MouseButton::Forward),
+            _ => Err(Error::ParseError(format!(
+                "Invalid mouse button: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+/// A single step of a macro.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MacroEvent {
+    /// Presses the keyboard key with the given HID usage code
+    KeyDown(u8),
+    /// Releases the keyboard key with the given HID usage code
+    KeyUp(u8),
+    ButtonDown(MouseButton),
+    ButtonUp(MouseButton),
+    /// Waits for the given number of milliseconds
+    Delay(u16),
+}
+
+impl MacroEvent {
+    /// Creates a delay event, saturating at the longest delay that can be encoded.
+    pub fn delay(duration: Duration) -> Self {
+        MacroEvent::Delay(duration.as_millis().min(u16::MAX as u128) as u16)
+    }
+
+    fn as_bytes(&self) -> [u8; MACRO_EVENT_LEN] {
+        match *self {
+            MacroEvent::KeyDown(usage) => [0x1, 0x0, usage],
+            MacroEvent::KeyUp(usage) => [0x2, 0x0, usage],
+            MacroEvent::ButtonDown(button) => [0x3, 0x0, button as u8],
+            MacroEvent::ButtonUp(button) => [0x4, 0x0, button as u8],
+            MacroEvent::Delay(ms) => {
+                let [high, low] = ms.to_be_bytes();
+                [0x5, high, low]
+            }
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for MacroEvent {
+    type Error = Error;
+
+    fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
+        match *raw {
+            [0x1, _, usage] => Ok(MacroEvent::KeyDown(usage)),
+            [0x2, _, usage] => Ok(MacroEvent::KeyUp(usage)),
+            [0x3, _, button] => Ok(MacroEvent::ButtonDown(button.try_into()?)),
+            [0x4, _, button] => Ok(MacroEvent::ButtonUp(button.try_into()?)),
+            [0x5, high, low] => Ok(MacroEvent::Delay(u16::from_be_bytes([high, low]))),
+            _ => Err(Error::ParseError(format!(
+                "Invalid macro event: {:02X?}",
+                raw
+            ))),
+        }
+    }
+}
+
+/// How a macro is repeated when its button is pressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RepeatMode {
+    /// Plays the macro once per press
+    #[default]
+    Once,
+    /// Plays the macro the given number of times per press
+    Times(u8),
+    /// Plays the macro in a loop while the button is held
+    WhileHeld,
+    /// Starts playing the macro in a loop on a press and stops on the next one
+    Toggle,
+}
+
+/// A sequence of key, mouse button and delay events stored in a macro slot.
+///
+/// # Examples
+///
+/// ```
+/// use libatk_rs::macros::{Macro, MacroEvent, RepeatMode};
+///
+/// let mut m = Macro::new(RepeatMode::Times(3));
+/// m.push(MacroEvent::KeyDown(0x04)).unwrap();
+/// m.push(MacroEvent::Delay(20)).unwrap();
+/// m.push(MacroEvent::KeyUp(0x04)).unwrap();
+///
+/// let raw = m.as_bytes();
+/// assert_eq!(Macro::try_from(raw.as_slice()).unwrap(), m);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Macro {
+    pub repeat: RepeatMode,
+    pub events: Vec<MacroEvent>,
+}
+
+impl Macro {
+    /// Creates an empty macro.
+    pub fn new(repeat: RepeatMode) -> Self {
+        Self {
+            repeat,
+            events: Vec::new(),
+        }
+    }
+
+    /// Appends an event, checking that the macro still fits in a slot.
+    pub fn push(&mut self, event: MacroEvent) -> Result<(), Error> {
+        if self.events.len() == MAX_MACRO_EVENTS {
+            return Err(Error::InvalidSetting(format!(
+                "A macro holds at most {} events",
+                MAX_MACRO_EVENTS
+            )));
+        }
+
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Returns the number of bytes used by the encoded macro.
+    pub fn encoded_len(&self) -> usize {
+        MACRO_HEADER_LEN + self.events.len() * MACRO_EVENT_LEN + 1
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.events.len() > MAX_MACRO_EVENTS {
+            return Err(Error::InvalidSetting(format!(
+                "Macro too large: {} bytes, the slot holds {}",
+                self.encoded_len(),
+                MACRO_SLOT_LEN
+            )));
+        }
+        if self.repeat == RepeatMode::Times(0) {
+            return Err(Error::InvalidSetting(
+                "A macro must be repeated at least once".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the header and the events of the macro, including the checksum.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let (mode, count) = match self.repeat {
+            RepeatMode::Once => (0x0, 0x1),
+            RepeatMode::Times(count) => (0x1, count),
+            RepeatMode::WhileHeld => (0x2, 0x0),
+            RepeatMode::Toggle => (0x3, 0x0),
+        };
+
+        let mut raw = vec![mode, count];
+        raw.extend((self.events.len() as u16).to_be_bytes());
+        for event in &self.events {
+            raw.extend(event.as_bytes());
+        }
+
+        with_checksum(&raw)
+    }
+
+    /// Decodes the number of events from a macro header.
+    fn event_count(header: &[u8]) -> Result<usize, Error> {
+        let count = u16::from_be_bytes([header[0x2], header[0x3]]) as usize;
+        if count > MAX_MACRO_EVENTS {
+            return Err(Error::ParseError(format!(
+                "Invalid macro event count: {}",
+                count
+            )));
+        }
+
+        Ok(count)
+    }
+}
+
+impl TryFrom<&[u8]> for Macro {
+    type Error = Error;
+
+    /// Decodes a macro from the start of a slot. Bytes past the checksum are ignored.
+    fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
+        if raw.len() < MACRO_HEADER_LEN + 1 {
+            return Err(Error::InvalidBufferLength {
+                expected: MACRO_HEADER_LEN + 1,
+                actual: raw.len(),
+            });
+        }
+
+        let len = MACRO_HEADER_LEN + Macro::event_count(raw)? * MACRO_EVENT_LEN + 1;
+        if raw.len() < len {
+            return Err(Error::InvalidBufferLength {
+                expected: len,
+                actual: raw.len(),
+            });
+        }
+
+        let values = verify_checksum(&raw[..len])?;
+        let repeat = match values[..2] {
+            [0x0, _] => RepeatMode::Once,
+            [0x1, count] => RepeatMode::Times(count),
+            [0x2, _] => RepeatMode::WhileHeld,
+            [0x3, _] => RepeatMode::Toggle,
+            _ => {
+                return Err(Error::ParseError(format!(
+                    "Invalid macro repeat mode: {:#04x}",
+                    values[0]
+                )))
+            }
+        };
+        let events = values[MACRO_HEADER_LEN..]
+            .chunks(MACRO_EVENT_LEN)
+            .map(MacroEvent::try_from)
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self { repeat, events })
+    }
+}
+
+fn macro_address(index: usize) -> Result<u16, Error> {
+    if index >= MAX_MACROS {
+        return Err(Error::InvalidSetting(format!(
+            "Macro index must be below {}: {}",
+            MAX_MACROS, index
+        )));
+    }
+
+    Ok(EEPROMAddress::Macro0.value() + (index * MACRO_SLOT_LEN) as u16)
+}
+
+impl<T: Transport> Device<T> {
+    /// Downloads the macro stored in the slot at `index`, `None` if the slot is unused.
+    ///
+    /// Only the bytes used by the macro are transferred.
+    pub fn download_macro(&self, index: usize) -> Result<Option<Macro>, Error> {
+        let address = macro_address(index)?;
+        let mut raw = self.read_eeprom(address, MACRO_HEADER_LEN)?;
+        if raw.iter().all(|&byte| byte == 0x0) || raw.iter().all(|&byte| byte == 0xff) {
+            return Ok(None);
+        }
+
+        let remaining = Macro::event_count(&raw)? * MACRO_EVENT_LEN + 1;
+        raw.extend(self.read_eeprom(address + MACRO_HEADER_LEN as u16, remaining)?);
+
+        Macro::try_from(raw.as_slice()).map(Some)
+    }
+
+    /// Uploads a macro to the slot at `index`, split into as many `SetEEPROM` commands as needed.
+    ///
+    /// The slot is marked unused while the events are written and the header goes in last, so an
+    /// interrupted upload leaves an empty slot rather than a half-written macro.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::buttons::ButtonAction;
+    /// use libatk_rs::macros::{Macro, MacroEvent, MouseButton, RepeatMode};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let mut double_click = Macro::new(RepeatMode::Once);
+    /// for _ in 0..2 {
+    ///     double_click.push(MacroEvent::ButtonDown(MouseButton::Left)).unwrap();
+    ///     double_click.push(MacroEvent::Delay(10)).unwrap();
+    ///     double_click.push(MacroEvent::ButtonUp(MouseButton::Left)).unwrap();
+    ///     double_click.push(MacroEvent::Delay(10)).unwrap();
+    /// }
+    /// // 29 bytes, written with three commands
+    /// device.upload_macro(1, &double_click).unwrap();
+    /// device.set_button(3, ButtonAction::Macro(1)).unwrap();
+    /// assert_eq!(device.download_macro(1).unwrap(), Some(double_click));
+    ///
+    /// device.clear_macro(1).unwrap();
+    /// assert_eq!(device.download_macro(1).unwrap(), None);
+    /// ```
+    pub fn upload_macro(&self, index: usize, m: &Macro) -> Result<(), Error> {
+        m.validate()?;
+        let address = macro_address(index)?;
+        let raw = m.as_bytes();
+
+        self.clear_macro(index)?;
+        self.write_eeprom(address + MACRO_HEADER_LEN as u16, &raw[MACRO_HEADER_LEN..])?;
+        self.write_eeprom(address, &raw[..MACRO_HEADER_LEN])
+    }
+
+    /// Marks the slot at `index` as unused.
+    pub fn clear_macro(&self, index: usize) -> Result<(), Error> {
+        self.write_eeprom(macro_address(index)?, &[0u8; MACRO_HEADER_LEN])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        device::RetryPolicy,
+        eeprom::Eeprom,
+        simulator::{Fault, Simulator},
+    };
+
+    fn typing() -> Macro {
+        let mut m = Macro::new(RepeatMode::Times(3));
+        m.push(MacroEvent::KeyDown(0x04)).unwrap();
+        m.push(MacroEvent::Delay(300)).unwrap();
+        m.push(MacroEvent::KeyUp(0x04)).unwrap();
+        m
+    }
+
+    #[test]
+    fn slots_use_the_atk_layout() {
+        let raw = [
+            0x1, 0x3, 0x0, 0x3, // Repeated 3 times, 3 events
+            0x1, 0x0, 0x4, // A down
+            0x5, 0x1, 0x2c, // 300 ms
+            0x2, 0x0, 0x4, // A up
+            0x11,
+        ];
+        assert_eq!(typing().as_bytes(), raw);
+
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.upload_macro(2, &typing()).unwrap();
+        assert_eq!(device.transport().eeprom()[0x600..0x600 + raw.len()], raw);
+    }
+
+    #[test]
+    fn full_slots_round_trip() {
+        let mut m = Macro::new(RepeatMode::Toggle);
+        for i in 0..MAX_MACRO_EVENTS {
+            m.push(MacroEvent::Delay(i as u16)).unwrap();
+        }
+        assert!(m.push(MacroEvent::Delay(0)).is_err());
+
+        // The last slot ends with the EEPROM
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.upload_macro(MAX_MACROS - 1, &m).unwrap();
+        assert_eq!(device.download_macro(MAX_MACROS - 1).unwrap(), Some(m));
+    }
+
+    #[test]
+    fn interrupted_uploads_leave_an_empty_slot() {
+        let mut device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_retry_policy(RetryPolicy::NONE);
+        device.upload_macro(0, &typing()).unwrap();
+
+        let mut m = typing();
+        m.push(MacroEvent::Delay(10)).unwrap();
+        // The header is cleared and the first chunk of events written, then reading it back fails
+        for _ in 0..3 {
+            device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        }
+        device.transport().push_fault(Fault::DropReply);
+        assert!(device.upload_macro(0, &m).is_err());
+        assert_eq!(device.download_macro(0).unwrap(), None);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::transport::ConnectionKind;
+
+/// Capabilities of a supported ATK based mouse.
+///
+/// Higher level APIs use the model attached to a [`Device`](crate::device::Device) to reject settings
+/// the hardware does not support instead of writing them to the EEPROM.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceModel {
+    pub name: &'static str,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Whether this VID/PID belongs to the mouse itself or to its receiver
+    pub connection: ConnectionKind,
+    pub max_dpi: u32,
+    /// Supported report rates in Hz
+    pub report_rates: &'static [u16],
+    /// Number of DPI stages that can be configured
+    pub dpi_stages: u8,
+    /// Number of remappable buttons
+    pub buttons: u8,
+    /// Number of RGB lighting zones
+    pub rgb_zones: u8,
+    /// Longest key debounce time in milliseconds
+    pub max_debounce_ms: u8,
+    pub has_4k_mode: bool,
+    pub has_far_distance_mode: bool,
+}
+
+impl DeviceModel {
+    /// Looks up the model with the given vendor and product IDs in [`MODELS`].
+    pub fn find(vendor_id: u16, product_id: u16) -> Option<&'static DeviceModel> {
+        MODELS
+            .iter()
+            .find(|model| model.vendor_id == vendor_id && model.product_id == product_id)
+    }
+
+    /// Returns true if the model can poll at the given rate.
+    pub fn supports_report_rate(&self, hz: u16) -> bool {
+        self.report_rates.contains(&hz)
+    }
+}
+
+static RATES_1K: &[u16] = &[125, 250, 500, 1000];
+static RATES_4K: &[u16] = &[125, 250, 500, 1000, 2000, 4000];
+static RATES_8K: &[u16] = &[125, 250, 500, 1000, 2000, 4000, 8000];
+
+/// Every known ATK based mouse, wired and receiver entries are listed separately.
+pub static MODELS: &[DeviceModel] = &[
+    DeviceModel {
+        name: "VXE Dragonfly R1",
+        vendor_id: 0x3554,
+        product_id: 0xf58a,
+        connection: ConnectionKind::Dongle,
+        max_dpi: 26000,
+        report_rates: RATES_1K,
+        dpi_stages: 8,
+        buttons: 6,
+        rgb_zones: 1,
+        max_debounce_ms: 20,
+        has_4k_mode: false,
+        has_far_distance_mode: false,
+    },
+    DeviceModel {
+        name: "VXE Dragonfly R1",
+        vendor_id: 0x3554,
+        product_id: 0xf58b,
+        connection: ConnectionKind::Wired,
+        max_dpi: 26000,
+        report_rates: RATES_1K,
+        dpi_stages: 8,
+        buttons: 6,
+        rgb_zones: 1,
+        max_debounce_ms: 20,
+        has_4k_mode: false,
+        has_far_distance_mode: false,
+    },
+    DeviceModel {
+        name: "VXE Dragonfly R1 Pro",
+        vendor_id: 0x3554,
+        product_id: 0xf58e,
+        connection: ConnectionKind::Dongle,
+        max_dpi: 26000,
+        report_rates: RATES_4K,
+        dpi_stages: 8,
+        buttons: 6,
+        rgb_zones: 1,
+        max_debounce_ms: 30,
+        has_4k_mode: true,
+        has_far_distance_mode: true,
+    },
+    DeviceModel {
+        name: "VXE Dragonfly R1 Pro",
+        vendor_id: 0x3554,
+        product_id: 0xf58f,
+        connection: ConnectionKind::Wired,
+        max_dpi: 26000,
+        report_rates: RATES_4K,
+        dpi_stages: 8,
+        buttons: 6,
+        rgb_zones: 1,
+        max_debounce_ms: 30,
+        has_4k_mode: true,
+        has_far_distance_mode: true,
+    },
+    DeviceModel {
+        name: "ATK A9 Ultra",
+        vendor_id: 0x373b,
+        product_id: 0x1040,
+        connection: ConnectionKind::Dongle,
+        max_dpi: 30000,
+        report_rates: RATES_8K,
+        dpi_stages: 8,
+        buttons: 5,
+        rgb_zones: 1,
+        max_debounce_ms: 30,
+        has_4k_mode: true,
+        has_far_distance_mode: true,
+    },
+    DeviceModel {
+        name: "ATK A9 Ultra",
+        vendor_id: 0x373b,
+        product_id: 0x1041,
+        connection: ConnectionKind::Wired,
+        max_dpi: 30000,
+        report_rates: RATES_8K,
+        dpi_stages: 8,
+        buttons: 5,
+        rgb_zones: 1,
+        max_debounce_ms: 30,
+        has_4k_mode: true,
+        has_far_distance_mode: true,
+    },
+];
@@ -0,0 +1,239 @@
+use crate::{
+    device::Device,
+    lighting::check_rgb_support,
+    transport::Transport,
+    types::{check_range, EEPROMAddress, Error},
+};
+
+/*
+Each setting is a single value followed by its checksum:
+┌─────────┬───────────────────┬─────────────────────────────────────────┐
+│ Address │ Setting           │ Value                                   │
+├─────────┼───────────────────┼─────────────────────────────────────────┤
+│  0xa9   │ StabilizationTime │ Key debounce time in ms                 │
+│  0xad   │ CloseLedTime      │ Idle time before the LEDs turn off, raw │
+│  0xb3   │ MoveCloseLights   │ 0 off, 1 on                             │
+│  0xbb   │ RfTxTime          │ Radio transmit timing, raw              │
+└─────────┴───────────────────┴─────────────────────────────────────────┘
+The unit of `CloseLedTime` and `RfTxTime` is not documented by the firmware, both are exposed as is
+until their encoding is known. Only `StabilizationTime` is known to be in milliseconds.
+*/
+
+/// Longest debounce time accepted when the model of the device is unknown, in milliseconds
+pub const DEFAULT_MAX_DEBOUNCE_MS: u8 = 20;
+
+/// Key debounce settings, stored at `StabilizationTime`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DebounceSettings {
+    /// Time a button must be stable before a press or release is reported, stored as is
+    pub debounce_ms: u8,
+}
+
+/// Power management settings, stored at `CloseLedTime`, `MoveCloseLights` and `RfTxTime`.
+///
+/// The timings are raw bytes without a known unit or valid range, so they are not converted or
+/// validated. Prefer values read with [`Device::get_power_settings`] over made up ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerSettings {
+    /// Raw value of `CloseLedTime`, how long the mouse stays idle before the LEDs turn off
+    pub close_led_time: u8,
+    /// Turns the LEDs off while the mouse is moving, stored as `MoveCloseLights`
+    pub lights_off_on_move: bool,
+    /// Raw value of `RfTxTime`, the radio transmit timing
+    pub rf_tx_time: u8,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            close_led_time: 60,
+            lights_off_on_move: false,
+            rf_tx_time: 1,
+        }
+    }
+}
+
+static POWER_ADDRESSES: [EEPROMAddress; 3] = [
+    EEPROMAddress::CloseLedTime,
+    EEPROMAddress::MoveCloseLights,
+    EEPROMAddress::RfTxTime,
+];
+
+impl<T: Transport> Device<T> {
+    /// Reads the key debounce settings.
+    pub fn get_debounce_settings(&self) -> Result<DebounceSettings, Error> {
+        Ok(DebounceSettings {
+            debounce_ms: self.get_checked_value(EEPROMAddress::StabilizationTime)?,
+        })
+    }
+
+    /// Writes the key debounce settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the settings were written.
+    /// * `Err(Error::InvalidSetting)` if the debounce time is longer than the model supports.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::power::DebounceSettings;
+    ///
+    /// let mut device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// device.set_model(DeviceModel::find(0x3554, 0xf58a));
+    ///
+    /// let settings = DebounceSettings { debounce_ms: 8 };
+    /// device.set_debounce_settings(&settings).unwrap();
+    /// assert_eq!(device.get_debounce_settings().unwrap(), settings);
+    /// assert!(device.set_debounce_settings(&DebounceSettings { debounce_ms: 50 }).is_err());
+    /// ```
+    pub fn set_debounce_settings(&self, settings: &DebounceSettings) -> Result<(), Error> {
+        let max = self
+            .model()
+            .map_or(DEFAULT_MAX_DEBOUNCE_MS, |model| model.max_debounce_ms);
+        check_range("Debounce time", settings.debounce_ms, &(0..=max))?;
+
+        self.set_checked_value(EEPROMAddress::StabilizationTime, settings.debounce_ms)
+    }
+
+    /// Reads the power management settings.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::power::PowerSettings;
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let settings = PowerSettings {
+    ///     close_led_time: 30,
+    ///     lights_off_on_move: true,
+    ///     ..Default::default()
+    /// };
+    /// device.set_power_settings(&settings).unwrap();
+    /// assert_eq!(device.get_power_settings().unwrap(), settings);
+    /// ```
+    pub fn get_power_settings(&self) -> Result<PowerSettings, Error> {
+        let mut raw = [0u8; 3];
+        for (value, address) in raw.iter_mut().zip(POWER_ADDRESSES) {
+            *value = self.get_checked_value(address)?;
+        }
+
+        Ok(PowerSettings {
+            close_led_time: raw[0],
+            lights_off_on_move: raw[1] != 0,
+            rf_tx_time: raw[2],
+        })
+    }
+
+    /// Writes the power management settings, each value along with its checksum.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the settings were written.
+    /// * `Err(Error::InvalidSetting)` if the LEDs should turn off on move but the model has no RGB lighting.
+    pub fn set_power_settings(&self, settings: &PowerSettings) -> Result<(), Error> {
+        if settings.lights_off_on_move {
+            check_rgb_support(self)?;
+        }
+
+        let raw = [
+            settings.close_led_time,
+            settings.lights_off_on_move as u8,
+            settings.rf_tx_time,
+        ];
+        for (value, address) in raw.into_iter().zip(POWER_ADDRESSES) {
+            self.set_checked_value(address, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eeprom::Eeprom, model::DeviceModel, simulator::Simulator, transport::ConnectionKind,
+    };
+
+    static NO_RGB: DeviceModel = DeviceModel {
+        name: "Unlit mouse",
+        vendor_id: 0x0,
+        product_id: 0x0,
+        connection: ConnectionKind::Wired,
+        max_dpi: 26000,
+        report_rates: &[1000],
+        dpi_stages: 8,
+        buttons: 6,
+        rgb_zones: 0,
+        max_debounce_ms: 20,
+        has_4k_mode: false,
+        has_far_distance_mode: false,
+    };
+
+    #[test]
+    fn settings_use_the_atk_layout() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device
+            .set_debounce_settings(&DebounceSettings { debounce_ms: 8 })
+            .unwrap();
+        device
+            .set_power_settings(&PowerSettings {
+                close_led_time: 0x3c,
+                lights_off_on_move: true,
+                rf_tx_time: 0x4,
+            })
+            .unwrap();
+
+        let eeprom = device.transport().eeprom();
+        assert_eq!(eeprom[0xa9..0xab], [0x8, 0x4d]);
+        assert_eq!(eeprom[0xad..0xaf], [0x3c, 0x19]);
+        assert_eq!(eeprom[0xb3..0xb5], [0x1, 0x54]);
+        assert_eq!(eeprom[0xbb..0xbd], [0x4, 0x51]);
+    }
+
+    #[test]
+    fn debounce_is_limited_per_model() {
+        let settings = DebounceSettings { debounce_ms: 25 };
+
+        let mut device = Device::from_transport(Simulator::<Eeprom>::new());
+        assert!(matches!(
+            device.set_debounce_settings(&settings),
+            Err(Error::InvalidSetting(_))
+        ));
+
+        device.set_model(DeviceModel::find(0x3554, 0xf58a));
+        assert!(device.set_debounce_settings(&settings).is_err());
+
+        device.set_model(DeviceModel::find(0x3554, 0xf58e));
+        device.set_debounce_settings(&settings).unwrap();
+        assert_eq!(device.get_debounce_settings().unwrap(), settings);
+    }
+
+    #[test]
+    fn lights_off_on_move_needs_rgb_lighting() {
+        let mut device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_model(Some(&NO_RGB));
+
+        assert!(matches!(
+            device.set_power_settings(&PowerSettings {
+                lights_off_on_move: true,
+                ..Default::default()
+            }),
+            Err(Error::InvalidSetting(_))
+        ));
+        assert!(device.transport().received().is_empty());
+
+        device
+            .set_power_settings(&PowerSettings::default())
+            .unwrap();
+        assert_eq!(
+            device.get_power_settings().unwrap(),
+            PowerSettings::default()
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    buttons::ButtonAction,
+    device::Device,
+    dpi::DpiStages,
+    lighting::{ArticleLamp, DpiLighting},
+    macros::{Macro, MAX_MACROS},
+    power::{DebounceSettings, PowerSettings},
+    report_rate::ReportRate,
+    sensor::SensorSettings,
+    shortcut::{KeyShortcut, MAX_SHORTCUTS},
+    transport::Transport,
+    types::Error,
+};
+use std::collections::BTreeMap;
+
+/// The whole configuration of a mouse, read and applied through the typed APIs.
+///
+/// With the `serde` feature enabled, profiles can be stored in any serde format such as TOML or JSON.
+///
+/// # Examples
+///
+/// ```
+/// # use libatk_rs::prelude::*;
+/// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+/// use libatk_rs::buttons::ButtonAction;
+/// use libatk_rs::dpi::{DpiStage, DpiStages};
+/// use libatk_rs::lighting::{ArticleLamp, ArticleLampEffect, DpiLighting, DpiLightingEffect};
+/// use libatk_rs::power::{DebounceSettings, PowerSettings};
+/// use libatk_rs::profile::Profile;
+/// use libatk_rs::sensor::SensorSettings;
+///
+/// let mut device = Device::from_transport(Simulator::<Eeprom>::new());
+/// device.set_model(DeviceModel::find(0x3554, 0xf58a));
+///
+/// let profile = Profile {
+///     report_rate: ReportRate::Hz1000,
+///     dpi: DpiStages {
+///         active: 0,
+///         stages: vec![DpiStage::new(800, Rgb::new(0xff, 0, 0))],
+///     },
+///     dpi_lighting: Some(DpiLighting {
+///         effect: DpiLightingEffect::Steady,
+///         brightness: 5,
+///         speed: 3,
+///         enabled: true,
+///     }),
+///     article_lamp: Some(ArticleLamp {
+///         color: Rgb::new(0, 0xff, 0),
+///         effect: ArticleLampEffect::Breathing,
+///         brightness: 10,
+///         breathing_speed: 2,
+///         energy_saving: true,
+///     }),
+///     sensor: SensorSettings {
+///         motion_sync: true,
+///         ..Default::default()
+///     },
+///     debounce: DebounceSettings { debounce_ms: 4 },
+///     power: PowerSettings::default(),
+///     buttons: vec![
+///         ButtonAction::LeftClick,
+///         ButtonAction::RightClick,
+///         ButtonAction::MiddleClick,
+///         ButtonAction::Back,
+///         ButtonAction::Forward,
+///         ButtonAction::Shortcut(0),
+///     ],
+///     shortcuts: [(0, "Ctrl+C".parse().unwrap())].into(),
+///     macros: Default::default(),
+/// };
+/// profile.apply_to(&device).unwrap();
+/// # #[cfg(feature = "serde")]
+/// # {
+/// let text = toml::to_string(&profile).unwrap();
+/// assert_eq!(toml::from_str::<Profile>(&text).unwrap(), profile);
+/// let json = serde_json::to_string(&profile).unwrap();
+/// assert_eq!(serde_json::from_str::<Profile>(&json).unwrap(), profile);
+/// # }
+///
+/// assert_eq!(Profile::read_from(&device).unwrap(), profile);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+    pub report_rate: ReportRate,
+    pub dpi: DpiStages,
+    /// `None` for models without RGB lighting, left untouched when applied
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub dpi_lighting: Option<DpiLighting>,
+    /// `None` for models without RGB lighting, left untouched when applied
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub article_lamp: Option<ArticleLamp>,
+    /// May be left out of a hand-written profile, [`SensorSettings::default`] is then applied
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sensor: SensorSettings,
+    pub debounce: DebounceSettings,
+    pub power: PowerSettings,
+    /// Actions of the buttons, starting from the first one
+    pub buttons: Vec<ButtonAction>,
+    /// Used shortcut slots by index, the other slots are cleared when applied
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub shortcuts: BTreeMap<u8, KeyShortcut>,
+    /// Used macro slots by index, the other slots are cleared when applied
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub macros: BTreeMap<u8, Macro>,
+}
+
+impl Profile {
+    /// Reads the configuration of the device.
+    pub fn read_from<T: Transport>(device: &Device<T>) -> Result<Self, Error> {
+        let has_rgb = device.model().map_or(true, |model| model.rgb_zones > 0);
+
+        let mut shortcuts = BTreeMap::new();
+        for index in 0..MAX_SHORTCUTS {
+            if let Some(shortcut) = device.get_shortcut(index)? {
+                shortcuts.insert(index as u8, shortcut);
+            }
+        }
+
+        let mut macros = BTreeMap::new();
+        for index in 0..MAX_MACROS {
+            if let Some(m) = device.download_macro(index)? {
+                macros.insert(index as u8, m);
+            }
+        }
+
+        Ok(Self {
+            report_rate: device.get_report_rate()?,
+            dpi: device.get_dpi_stages()?,
+            dpi_lighting: has_rgb.then(|| device.get_dpi_lighting()).transpose()?,
+            article_lamp: has_rgb.then(|| device.get_article_lamp()).transpose()?,
+            sensor: device.get_sensor_settings()?,
+            debounce: device.get_debounce_settings()?,
+            power: device.get_power_settings()?,
+            buttons: device.get_all_buttons()?,
+            shortcuts,
+            macros,
+        })
+    }
+
+    /// Writes the configuration to the device.
+    ///
+    /// Shortcut and macro slots missing from the profile are cleared if they are in use.
+    pub fn apply_to<T: Transport>(&self, device: &Device<T>) -> Result<(), Error> {
+        device.set_report_rate(self.report_rate)?;
+        device.set_dpi_stages(&self.dpi)?;
+        if let Some(lighting) = &self.dpi_lighting {
+            device.set_dpi_lighting(lighting)?;
+        }
+        if let Some(lamp) = &self.article_lamp {
+            device.set_article_lamp(lamp)?;
+        }
+        device.set_sensor_settings(&self.sensor)?;
+        device.set_debounce_settings(&self.debounce)?;
+        device.set_power_settings(&self.power)?;
+        for (index, &action) in self.buttons.iter().enumerate() {
+            device.set_button(index, action)?;
+        }
+
+        for index in 0..MAX_SHORTCUTS {
+            match self.shortcuts.get(&(index as u8)) {
+                Some(shortcut) => device.set_shortcut(index, shortcut)?,
+                None if device.get_shortcut(index)?.is_some() => device.clear_shortcut(index)?,
+                None => {}
+            }
+        }
+        for index in 0..MAX_MACROS {
+            match self.macros.get(&(index as u8)) {
+                Some(m) => device.upload_macro(index, m)?,
+                None if device.download_macro(index)?.is_some() => device.clear_macro(index)?,
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    /// The settings of a hand-written profile that have no default
+    static REQUIRED: &str = r#"
+        report_rate = "Hz1000"
+        buttons = ["LeftClick", "RightClick"]
+
+        [dpi]
+        active = 0
+
+        [[dpi.stages]]
+        x = 800
+        y = 800
+        color = { r = 255, g = 0, b = 0 }
+    "#;
+    static DEBOUNCE: &str = r#"
+        [debounce]
+        debounce_ms = 4
+    "#;
+    static POWER: &str = r#"
+        [power]
+        close_led_time = 60
+        lights_off_on_move = false
+        rf_tx_time = 1
+    "#;
+
+    #[test]
+    fn sensor_settings_are_optional_in_hand_written_profiles() {
+        let text = [REQUIRED, DEBOUNCE, POWER].concat();
+        let profile: Profile = toml::from_str(&text).unwrap();
+        assert_eq!(profile.sensor, SensorSettings::default());
+        assert!(profile.sensor.sensor_enable);
+    }
+
+    #[test]
+    fn debounce_and_power_settings_are_required() {
+        for text in [[REQUIRED, DEBOUNCE].concat(), [REQUIRED, POWER].concat()] {
+            assert!(toml::from_str::<Profile>(&text).is_err());
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{
+    device::Device,
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+
+/// Polling rate of the mouse.
+///
+/// The discriminant is the value stored at `EEPROMAddress::ReportRate`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReportRate {
+    Hz125 = 0x8,
+    Hz250 = 0x4,
+    Hz500 = 0x2,
+    Hz1000 = 0x1,
+    Hz2000 = 0x10,
+    Hz4000 = 0x20,
+    Hz8000 = 0x40,
+}
+
+impl ReportRate {
+    /// Returns the rate in Hz.
+    pub fn hz(&self) -> u16 {
+        match self {
+            ReportRate::Hz125 => 125,
+            ReportRate::Hz250 => 250,
+            ReportRate::Hz500 => 500,
+            ReportRate::Hz1000 => 1000,
+            ReportRate::Hz2000 => 2000,
+            ReportRate::Hz4000 => 4000,
+            ReportRate::Hz8000 => 8000,
+        }
+    }
+
+    /// Returns the rate matching the given frequency in Hz, if any.
+    pub fn from_hz(hz: u16) -> Option<Self> {
+        match hz {
+            125 => Some(ReportRate::Hz125),
+            250 => Some(ReportRate::Hz250),
+            500 => Some(ReportRate::Hz500),
+            1000 => Some(ReportRate::Hz1000),
+            2000 => Some(ReportRate::Hz2000),
+            4000 => Some(ReportRate::Hz4000),
+            8000 => Some(ReportRate::Hz8000),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<u8> for ReportRate {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x8 => Ok(ReportRate::Hz125),
+            0x4 => Ok(ReportRate::Hz250),
+            0x2 => Ok(ReportRate::Hz500),
+            0x1 => Ok(ReportRate::Hz1000),
+            0x10 => Ok(ReportRate::Hz2000),
+            0x20 => Ok(ReportRate::Hz4000),
+            0x40 => Ok(ReportRate::Hz8000),
+            _ => Err(Error::ParseError(format!(
+                "Invalid report rate: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} Hz", self.hz())
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the report rate, verifying its checksum byte.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use libatk_rs::prelude::*;
+    /// let device = Device::open_any().expect("No supported device found");
+    /// println!("Polling at {}", device.get_report_rate().expect("Failed to read report rate"));
+    /// ```
+    pub fn get_report_rate(&self) -> Result<ReportRate, Error> {
+        self.get_checked_value(EEPROMAddress::ReportRate)?
+            .try_into()
+    }
+
+    /// Writes the report rate along with its checksum byte.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the rate was written.
+    /// * `Err(Error::InvalidSetting)` if the model of the device does not support the rate.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// device.set_report_rate(ReportRate::Hz500).unwrap();
+    /// assert_eq!(device.get_report_rate().unwrap(), ReportRate::Hz500);
+    /// ```
+    pub fn set_report_rate(&self, rate: ReportRate) -> Result<(), Error> {
+        if let Some(model) = self.model() {
+            if !model.supports_report_rate(rate.hz()) {
+                return Err(Error::InvalidSetting(format!(
+                    "{} does not support a report rate of {}",
+                    model.name, rate
+                )));
+            }
+        }
+
+        self.set_checked_value(EEPROMAddress::ReportRate, rate as u8)
+    }
+}
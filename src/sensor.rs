@@ -0,0 +1,244 @@
+use crate::{
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+
+/*
+Each setting is a single value followed by its checksum:
+┌─────────┬──────────────────┬──────────────────────────────────────────────┐
+│ Address │ Setting          │ Value                                        │
+├─────────┼──────────────────┼──────────────────────────────────────────────┤
+│  0x0a   │ SilentHeight     │ Lift-off distance, 0x1 1 mm, 0x2 2 mm        │
+│  0xab   │ MotionSync       │ 0 off, 1 on                                  │
+│  0xaf   │ LinearCorrection │ Angle snapping, 0 off, 1 on                  │
+│  0xb1   │ RippleControl    │ 0 off, 1 on                                  │
+│  0xb5   │ SensorEnable     │ 0 off, 1 on                                  │
+│  0xb7   │ SensorTime       │ Raw value                                    │
+│  0xb9   │ SensorMode       │ 0x0 low power, 0x1 standard, 0x2 competitive │
+└─────────┴──────────────────┴──────────────────────────────────────────────┘
+`CloseLedTime` and `MoveCloseLights` sit between the sensor settings and are left untouched.
+*/
+
+/// First address of the block holding every sensor setting but `SilentHeight`
+const SENSOR_BLOCK_START: EEPROMAddress = EEPROMAddress::MotionSync;
+/// Length of the block, from `MotionSync` to `SensorModeCRC`
+const SENSOR_BLOCK_LEN: usize =
+    (EEPROMAddress::SensorModeCRC.value() - SENSOR_BLOCK_START.value() + 1) as usize;
+
+/// Height above the surface at which the sensor stops tracking.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LiftOffDistance {
+    #[default]
+    Mm1 = 0x1,
+    Mm2 = 0x2,
+}
+
+impl TryFrom<u8> for LiftOffDistance {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x1 => Ok(LiftOffDistance::Mm1),
+            0x2 => Ok(LiftOffDistance::Mm2),
+            _ => Err(Error::ParseError(format!(
+                "Invalid lift-off distance: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Performance mode of the sensor, trading battery life for tracking performance.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SensorMode {
+    LowPower = 0x0,
+    #[default]
+    Standard = 0x1,
+    Competitive = 0x2,
+}
+
+impl TryFrom<u8> for SensorMode {
+    type Error = Error;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0x0 => Ok(SensorMode::LowPower),
+            0x1 => Ok(SensorMode::Standard),
+            0x2 => Ok(SensorMode::Competitive),
+            _ => Err(Error::ParseError(format!(
+                "Invalid sensor mode: {:#04x}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Tuning of the sensor.
+///
+/// The default matches a mouse fresh from the factory, with the sensor enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SensorSettings {
+    pub lift_off_distance: LiftOffDistance,
+    pub motion_sync: bool,
+    /// Straightens nearly horizontal and vertical movements, stored as `LinearCorrection`
+    pub angle_snapping: bool,
+    pub ripple_control: bool,
+    pub sensor_enable: bool,
+    /// Raw value of `SensorTime`
+    pub sensor_time: u8,
+    pub mode: SensorMode,
+}
+
+impl Default for SensorSettings {
+    fn default() -> Self {
+        SensorSettings {
+            lift_off_distance: LiftOffDistance::default(),
+            motion_sync: false,
+            angle_snapping: false,
+            ripple_control: false,
+            sensor_enable: true,
+            sensor_time: 0,
+            mode: SensorMode::default(),
+        }
+    }
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the sensor settings, verifying every checksum.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::sensor::{LiftOffDistance, SensorMode, SensorSettings};
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let settings = SensorSettings {
+    ///     lift_off_distance: LiftOffDistance::Mm2,
+    ///     motion_sync: true,
+    ///     mode: SensorMode::Competitive,
+    ///     ..Default::default()
+    /// };
+    /// device.set_sensor_settings(&settings).unwrap();
+    /// assert_eq!(device.get_sensor_settings().unwrap(), settings);
+    /// ```
+    pub fn get_sensor_settings(&self) -> Result<SensorSettings, Error> {
+        let lift_off_distance = self.get_checked_value(EEPROMAddress::SilentHeight)?;
+
+        let raw = self.read_eeprom(SENSOR_BLOCK_START.value(), SENSOR_BLOCK_LEN)?;
+        let value = |address: EEPROMAddress| {
+            let offset = (address.value() - SENSOR_BLOCK_START.value()) as usize;
+            verify_checksum(&raw[offset..offset + 2]).map(|value| value[0])
+        };
+
+        Ok(SensorSettings {
+            lift_off_distance: lift_off_distance.try_into()?,
+            motion_sync: value(EEPROMAddress::MotionSync)? != 0,
+            angle_snapping: value(EEPROMAddress::LinearCorrection)? != 0,
+            ripple_control: value(EEPROMAddress::RippleControl)? != 0,
+            sensor_enable: value(EEPROMAddress::SensorEnable)? != 0,
+            sensor_time: value(EEPROMAddress::SensorTime)?,
+            mode: value(EEPROMAddress::SensorMode)?.try_into()?,
+        })
+    }
+
+    /// Writes the sensor settings, each value along with its checksum.
+    ///
+    /// `SilentHeight` is written on its own, the other settings are read, updated and written back as
+    /// one block from `MotionSync` to `SensorModeCRC` so that `CloseLedTime` and `MoveCloseLights`
+    /// keep their values. This is not atomic: the block takes two reports, an error part way through
+    /// leaves the settings partly applied, though every value still matches its checksum.
+    pub fn set_sensor_settings(&self, settings: &SensorSettings) -> Result<(), Error> {
+        self.set_checked_value(
+            EEPROMAddress::SilentHeight,
+            settings.lift_off_distance as u8,
+        )?;
+
+        let mut raw = self.read_eeprom(SENSOR_BLOCK_START.value(), SENSOR_BLOCK_LEN)?;
+        for (address, value) in [
+            (EEPROMAddress::MotionSync, settings.motion_sync as u8),
+            (
+                EEPROMAddress::LinearCorrection,
+                settings.angle_snapping as u8,
+            ),
+            (EEPROMAddress::RippleControl, settings.ripple_control as u8),
+            (EEPROMAddress::SensorEnable, settings.sensor_enable as u8),
+            (EEPROMAddress::SensorTime, settings.sensor_time),
+            (EEPROMAddress::SensorMode, settings.mode as u8),
+        ] {
+            let offset = (address.value() - SENSOR_BLOCK_START.value()) as usize;
+            raw[offset..offset + 2].copy_from_slice(&with_checksum(&[value]));
+        }
+
+        self.write_eeprom(SENSOR_BLOCK_START.value(), &raw)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator};
+
+    #[test]
+    fn settings_use_the_atk_layout() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        // `CloseLedTime` and `MoveCloseLights`
+        device.transport().write_eeprom(0xad, &[0x3c, 0x19]);
+        device.transport().write_eeprom(0xb3, &[0x1, 0x54]);
+
+        let settings = SensorSettings {
+            lift_off_distance: LiftOffDistance::Mm2,
+            motion_sync: true,
+            angle_snapping: false,
+            ripple_control: true,
+            sensor_enable: true,
+            sensor_time: 0x20,
+            mode: SensorMode::Competitive,
+        };
+        device.set_sensor_settings(&settings).unwrap();
+
+        let eeprom = device.transport().eeprom();
+        assert_eq!(eeprom[0x0a..0x0c], [0x2, 0x53]);
+        assert_eq!(
+            eeprom[0xab..0xbb],
+            [
+                0x1, 0x54, 0x3c, 0x19, 0x0, 0x55, 0x1, 0x54, 0x1, 0x54, 0x1, 0x54, 0x20, 0x35, 0x2,
+                0x53
+            ]
+        );
+        assert_eq!(device.get_sensor_settings().unwrap(), settings);
+    }
+
+    #[test]
+    fn sensor_is_enabled_by_default() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device
+            .set_sensor_settings(&SensorSettings::default())
+            .unwrap();
+
+        assert_eq!(device.transport().eeprom()[0xb5..0xb7], [0x1, 0x54]);
+        assert!(device.get_sensor_settings().unwrap().sensor_enable);
+    }
+
+    #[test]
+    fn corrupted_values_are_rejected() {
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device
+            .set_sensor_settings(&SensorSettings::default())
+            .unwrap();
+        device.transport().write_eeprom(0xb9, &[0x1, 0x55]);
+
+        assert!(matches!(
+            device.get_sensor_settings(),
+            Err(Error::ChecksumMismatch {
+                expected: 0x54,
+                actual: 0x55
+            })
+        ));
+    }
+}
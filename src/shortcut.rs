@@ -0,0 +1,371 @@
+use crate::{
+    buttons::Modifiers,
+    device::Device,
+    eeprom::{verify_checksum, with_checksum},
+    transport::Transport,
+    types::{EEPROMAddress, Error},
+};
+
+/*
+Each shortcut slot, `KeyShortcuts0` to `KeyShortcuts15`, is 0x20 bytes long:
+┌────────────┬───────────────┬──────────────────────┬─────────┬──────────┐
+│ Key Count  │ Modifier Mask │   HID Usage Codes    │ Padding │ Checksum │
+│   1 Byte   │    1 Byte     │  Key Count Bytes     │         │  1 Byte  │
+└────────────┴───────────────┴──────────────────────┴─────────┴──────────┘
+The checksum covers the 0x1f bytes preceding it. Unused slots are blank.
+*/
+
+/// Size of a shortcut slot in the EEPROM
+pub const SHORTCUT_SLOT_LEN: usize = 0x20;
+/// Number of shortcut slots
+pub const MAX_SHORTCUTS: usize = 16;
+/// Maximum number of non modifier keys in a combination, as in a HID boot keyboard report
+pub const MAX_SHORTCUT_KEYS: usize = 6;
+
+static MODIFIER_NAMES: [(&str, Modifiers); 12] = [
+    ("ctrl", Modifiers::LEFT_CTRL),
+    ("control", Modifiers::LEFT_CTRL),
+    ("shift", Modifiers::LEFT_SHIFT),
+    ("alt", Modifiers::LEFT_ALT),
+    ("gui", Modifiers::LEFT_GUI),
+    ("win", Modifiers::LEFT_GUI),
+    ("super", Modifiers::LEFT_GUI),
+    ("cmd", Modifiers::LEFT_GUI),
+    ("rctrl", Modifiers::RIGHT_CTRL),
+    ("rshift", Modifiers::RIGHT_SHIFT),
+    ("ralt", Modifiers::RIGHT_ALT),
+    ("rgui", Modifiers::RIGHT_GUI),
+];
+
+static KEY_NAMES: [(&str, u8); 40] = [
+    ("Enter", 0x28),
+    ("Esc", 0x29),
+    ("Backspace", 0x2a),
+    ("Tab", 0x2b),
+    ("Space", 0x2c),
+    ("-", 0x2d),
+    ("=", 0x2e),
+    ("[", 0x2f),
+    ("]", 0x30),
+    ("\\", 0x31),
+    (";", 0x33),
+    ("'", 0x34),
+    ("`", 0x35),
+    (",", 0x36),
+    (".", 0x37),
+    ("/", 0x38),
+    ("CapsLock", 0x39),
+    ("PrintScreen", 0x46),
+    ("ScrollLock", 0x47),
+    ("Pause", 0x48),
+    ("Insert", 0x49),
+    ("Home", 0x4a),
+    ("PageUp", 0x4b),
+    ("Delete", 0x4c),
+    ("End", 0x4d),
+    ("PageDown", 0x4e),
+    ("Right", 0x4f),
+    ("Left", 0x50),
+    ("Down", 0x51),
+    ("Up", 0x52),
+    ("NumLock", 0x53),
+    ("Menu", 0x65),
+    // Aliases, only used when parsing
+    ("Return", 0x28),
+    ("Escape", 0x29),
+    ("Del", 0x4c),
+    ("Ins", 0x49),
+    ("PgUp", 0x4b),
+    ("PgDn", 0x4e),
+    ("Minus", 0x2d),
+    ("Equal", 0x2e),
+];
+
+/// Returns the HID usage code of a key given by its name, e.g. `"A"`, `"5"`, `"F5"` or `"PageUp"`.
+///
+/// Names are case insensitive.
+pub fn key_usage(name: &str) -> Option<u8> {
+    let upper = name.to_ascii_uppercase();
+    let bytes = upper.as_bytes();
+
+    match bytes {
+        [c @ b'A'..=b'Z'] => return Some(c - b'A' + 0x04),
+        [b'0'] => return Some(0x27),
+        [c @ b'1'..=b'9'] => return Some(c - b'1' + 0x1e),
+        [b'F', ..] => {
+            if let Ok(n @ 1..=24) = upper[1..].parse::<u8>() {
+                return Some(if n <= 12 { 0x3a + n - 1 } else { 0x68 + n - 13 });
+            }
+        }
+        _ => {}
+    }
+
+    KEY_NAMES
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|&(_, usage)| usage)
+}
+
+/// Returns the name of a key given by its HID usage code, the inverse of [`key_usage`].
+pub fn key_name(usage: u8) -> Option<String> {
+    match usage {
+        0x04..=0x1d => Some(((usage - 0x04 + b'A') as char).to_string()),
+        0x1e..=0x26 => Some(((usage - 0x1e + b'1') as char).to_string()),
+        0x27 => Some("0".to_string()),
+        0x3a..=0x45 => Some(format!("F{}", usage - 0x3a + 1)),
+        0x68..=0x73 => Some(format!("F{}", usage - 0x68 + 13)),
+        _ => KEY_NAMES
+            .iter()
+            .find(|&&(_, code)| code == usage)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+/// A key combination bound to a button through [`ButtonAction::Shortcut`](crate::buttons::ButtonAction::Shortcut).
+///
+/// # Examples
+///
+/// ```
+/// use libatk_rs::buttons::Modifiers;
+/// use libatk_rs::shortcut::KeyShortcut;
+///
+/// let shortcut: KeyShortcut = "Ctrl+Shift+F5".parse().unwrap();
+/// assert_eq!(shortcut.modifiers, Modifiers::LEFT_CTRL | Modifiers::LEFT_SHIFT);
+/// assert_eq!(shortcut.keys, vec![0x3e]);
+/// assert_eq!(shortcut.to_string(), "Ctrl+Shift+F5");
+///
+/// // Keys without a name are printed as their usage code, which parses back
+/// let shortcut = KeyShortcut::new(Modifiers::LEFT_ALT, vec![0x87]).unwrap();
+/// assert_eq!(shortcut.to_string(), "Alt+0x87");
+/// assert_eq!("Alt+0x87".parse::<KeyShortcut>().unwrap(), shortcut);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyShortcut {
+    pub modifiers: Modifiers,
+    /// HID usage codes of the non modifier keys
+    pub keys: Vec<u8>,
+}
+
+impl KeyShortcut {
+    /// Creates a shortcut, checking that it fits in a slot.
+    pub fn new(modifiers: Modifiers, keys: Vec<u8>) -> Result<Self, Error> {
+        let shortcut = Self { modifiers, keys };
+        shortcut.validate()?;
+
+        Ok(shortcut)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.keys.len() > MAX_SHORTCUT_KEYS {
+            return Err(Error::InvalidSetting(format!(
+                "A shortcut holds at most {} keys: {}",
+                MAX_SHORTCUT_KEYS,
+                self.keys.len()
+            )));
+        }
+        if self.keys.is_empty() && self.modifiers == Modifiers::NONE {
+            return Err(Error::InvalidSetting("A shortcut needs a key".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Encodes the shortcut into its EEPROM slot, including the checksum.
+    pub fn as_bytes(&self) -> Vec<u8> {
+        let mut raw = vec![0u8; SHORTCUT_SLOT_LEN - 1];
+        raw[0x0] = self.keys.len() as u8;
+        raw[0x1] = self.modifiers.0;
+        raw[0x2..0x2 + self.keys.len()].copy_from_slice(&self.keys);
+
+        with_checksum(&raw)
+    }
+}
+
+impl TryFrom<&[u8]> for KeyShortcut {
+    type Error = Error;
+
+    fn try_from(raw: &[u8]) -> Result<Self, Self::Error> {
+        if raw.len() != SHORTCUT_SLOT_LEN {
+            return Err(Error::InvalidBufferLength {
+                expected: SHORTCUT_SLOT_LEN,
+                actual: raw.len(),
+            });
+        }
+
+        let values = verify_checksum(raw)?;
+        let count = values[0x0] as usize;
+        if count > MAX_SHORTCUT_KEYS {
+            return Err(Error::ParseError(format!(
+                "Invalid shortcut key count: {}",
+                count
+            )));
+        }
+
+        Ok(Self {
+            modifiers: Modifiers(values[0x1]),
+            keys: values[0x2..0x2 + count].to_vec(),
+        })
+    }
+}
+
+impl std::str::FromStr for KeyShortcut {
+    type Err = Error;
+
+    /// Parses a combination such as `"Ctrl+Shift+F5"`. Modifiers and keys are case insensitive and
+    /// can appear in any order. Keys without a name are given by their usage code, e.g. `"0x87"`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::NONE;
+        let mut keys = Vec::new();
+
+        for part in s.split('+').map(str::trim) {
+            if let Some(&(_, modifier)) = MODIFIER_NAMES
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(part))
+            {
+                modifiers |= modifier;
+            } else if let Some(usage) = key_usage(part) {
+                keys.push(usage);
+            } else if let Some(Ok(usage)) = part
+                .strip_prefix("0x")
+                .map(|hex| u8::from_str_radix(hex, 16))
+            {
+                keys.push(usage);
+            } else {
+                return Err(Error::ParseError(format!("Unknown key: {:?}", part)));
+            }
+        }
+
+        KeyShortcut::new(modifiers, keys)
+    }
+}
+
+impl std::fmt::Display for KeyShortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let modifiers = [
+            (Modifiers::LEFT_CTRL, "Ctrl"),
+            (Modifiers::LEFT_SHIFT, "Shift"),
+            (Modifiers::LEFT_ALT, "Alt"),
+            (Modifiers::LEFT_GUI, "Gui"),
+            (Modifiers::RIGHT_CTRL, "RCtrl"),
+            (Modifiers::RIGHT_SHIFT, "RShift"),
+            (Modifiers::RIGHT_ALT, "RAlt"),
+            (Modifiers::RIGHT_GUI, "RGui"),
+        ];
+
+        let parts: Vec<String> = modifiers
+            .iter()
+            .filter(|(modifier, _)| self.modifiers.contains(*modifier))
+            .map(|(_, name)| name.to_string())
+            .chain(
+                self.keys
+                    .iter()
+                    .map(|&usage| key_name(usage).unwrap_or_else(|| format!("{:#04x}", usage))),
+            )
+            .collect();
+
+        write!(f, "{}", parts.join("+"))
+    }
+}
+
+/// Shortcuts are serialized in their text form, e.g. `"Ctrl+Shift+F5"`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyShortcut {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyShortcut {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+fn shortcut_address(index: usize) -> Result<u16, Error> {
+    if index >= MAX_SHORTCUTS {
+        return Err(Error::InvalidSetting(format!(
+            "Shortcut index must be below {}: {}",
+            MAX_SHORTCUTS, index
+        )));
+    }
+
+    Ok(EEPROMAddress::KeyShortcuts0.value() + (index * SHORTCUT_SLOT_LEN) as u16)
+}
+
+impl<T: Transport> Device<T> {
+    /// Reads the shortcut stored in the slot at `index`, `None` if the slot is unused.
+    pub fn get_shortcut(&self, index: usize) -> Result<Option<KeyShortcut>, Error> {
+        let raw = self.read_eeprom(shortcut_address(index)?, SHORTCUT_SLOT_LEN)?;
+        if raw.iter().all(|&byte| byte == 0x0) || raw.iter().all(|&byte| byte == 0xff) {
+            return Ok(None);
+        }
+
+        KeyShortcut::try_from(raw.as_slice()).map(Some)
+    }
+
+    /// Stores a shortcut in the slot at `index`.
+    ///
+    /// A slot spans several commands, each of them is verified after being written.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::buttons::ButtonAction;
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// let shortcut = "Ctrl+Alt+Delete".parse().unwrap();
+    /// device.set_shortcut(3, &shortcut).unwrap();
+    /// device.set_button(4, ButtonAction::Shortcut(3)).unwrap();
+    /// assert_eq!(device.get_shortcut(3).unwrap(), Some(shortcut));
+    ///
+    /// device.clear_shortcut(3).unwrap();
+    /// assert_eq!(device.get_shortcut(3).unwrap(), None);
+    /// ```
+    pub fn set_shortcut(&self, index: usize, shortcut: &KeyShortcut) -> Result<(), Error> {
+        shortcut.validate()?;
+        self.write_eeprom(shortcut_address(index)?, &shortcut.as_bytes())
+    }
+
+    /// Marks the slot at `index` as unused.
+    pub fn clear_shortcut(&self, index: usize) -> Result<(), Error> {
+        self.write_eeprom(shortcut_address(index)?, &[0u8; SHORTCUT_SLOT_LEN])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, simulator::Simulator};
+
+    #[test]
+    fn slots_use_the_atk_layout() {
+        let shortcut: KeyShortcut = "Ctrl+C".parse().unwrap();
+        let mut raw = vec![0x1, 0x1, 0x6];
+        raw.resize(SHORTCUT_SLOT_LEN - 1, 0x0);
+        raw.push(0x4d);
+        assert_eq!(shortcut.as_bytes(), raw);
+
+        // The last slot ends right before `Macro0`
+        let device = Device::from_transport(Simulator::<Eeprom>::new());
+        device.set_shortcut(MAX_SHORTCUTS - 1, &shortcut).unwrap();
+        assert_eq!(device.transport().eeprom()[0x2e0..0x300], raw);
+        assert!(device.set_shortcut(MAX_SHORTCUTS, &shortcut).is_err());
+    }
+
+    #[test]
+    fn every_key_round_trips_through_text() {
+        for usage in 0..=u8::MAX {
+            let shortcut = KeyShortcut::new(Modifiers::RIGHT_GUI, vec![usage]).unwrap();
+            assert_eq!(
+                shortcut.to_string().parse::<KeyShortcut>().unwrap(),
+                shortcut,
+                "{}",
+                shortcut
+            );
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use crate::{
+    command::{Command, CommandDescriptor},
+    transport::{ConnectionKind, DeviceInfo, Transport},
+    types::{CommandId, Error},
+};
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub use crate::eeprom::EEPROM_SIZE;
+
+static STATUS_SUCCESS: u8 = 0x0;
+static STATUS_FAILURE: u8 = 0x1;
+static PAIR_IN_PROGRESS: u8 = 0x0;
+static PAIR_SUCCESS: u8 = 0x1;
+static PAIR_FAILED: u8 = 0x2;
+
+/// A fault injected into the next reply of the [`Simulator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The reply is never sent
+    DropReply,
+    /// The reply is sent with a corrupted checksum
+    BadChecksum,
+    /// The reply is sent after the given delay. If a read times out before that, the reply is
+    /// delivered to a later read.
+    Delay(Duration),
+    /// The command is rejected with the given status
+    Reject(u8),
+}
+
+/// An in-memory ATK mouse implementing the device side of the protocol.
+///
+/// The simulator is a [`Transport`], so a [`Device`](crate::device::Device) can be built on top of it
+/// to run the library without hardware. It emulates the EEPROM addressed like
+/// [`EEPROMAddress`](crate::types::EEPROMAddress) and answers the following commands with correct
+/// checksums and status bytes:
+///
+/// * `SetEEPROM` / `GetEEPROM`
+/// * `RestoreFactory`, which restores the EEPROM the simulator was created with
+/// * `GetBatteryLevel`, `GetMouseVersion` and `GetWirelessMouseOnline`
+/// * `SetWirelessDonglePair`, `GetWirelessDonglePairResult` and `DongleExitPair`
+/// * `GetCurrentConfig` / `SetCurrentConfig`
+///
+/// Other commands are rejected. While the mouse is set offline, only `GetWirelessMouseOnline` and the
+/// pairing commands are answered. Faults can be injected with [`Simulator::push_fault`].
+///
+/// The frame layout and checksums follow the descriptor `C`. Reads never block: without a pending
+/// reply they time out immediately.
+///
+/// # Examples
+///
+/// ```
+/// use libatk_rs::prelude::*;
+/// use libatk_rs::simulator::{Fault, Simulator};
+///
+/// #[derive(Command)]
+/// struct Eeprom;
+///
+/// let device = Device::from_transport(Simulator::<Eeprom>::new());
+///
+/// let mut command = Command::<Eeprom>::default();
+/// command.set_id(CommandId::SetEEPROM);
+/// command.set_eeprom_address(EEPROMAddress::CurrentDpi);
+/// command.set_data_len(2).unwrap();
+/// command.set_data_byte_with_checksum(0x3, 0).unwrap();
+/// device.execute(command).unwrap();
+/// assert_eq!(device.transport().eeprom()[0x4..0x6], [0x3, 0x52]);
+///
+/// // The first reply is lost, the retry policy of the device resends the command.
+/// device.transport().push_fault(Fault::DropReply);
+/// let mut command = Command::<Eeprom>::default();
+/// command.set_id(CommandId::GetEEPROM);
+/// command.set_eeprom_address(EEPROMAddress::CurrentDpi);
+/// command.set_data_len(2).unwrap();
+/// let response = device.execute(command).unwrap();
+/// assert_eq!(response.data()[..2], [0x3, 0x52]);
+/// ```
+pub struct Simulator<C: CommandDescriptor> {
+    state: Mutex<State>,
+    _cmd: PhantomData<fn() -> C>,
+}
+
+struct State {
+    eeprom: Vec<u8>,
+    factory: Vec<u8>,
+    info: DeviceInfo,
+    battery: [u8; 3],
+    version: Vec<u8>,
+    current_config: u8,
+    mouse_online: bool,
+    /// Polls answered before pairing succeeds, `None` if it never does
+    pair_after: Option<u32>,
+    /// Remaining polls of the current pairing attempt
+    pairing: Option<Option<u32>>,
+    faults: VecDeque<Fault>,
+    /// Reports waiting to be read along with the delay before they arrive
+    replies: VecDeque<(Vec<u8>, Duration)>,
+    received: Vec<Vec<u8>>,
+}
+
+impl<C: CommandDescriptor> Default for Simulator<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: CommandDescriptor> Simulator<C> {
+    /// Creates a simulator with a zeroed EEPROM.
+    pub fn new() -> Self {
+        Self::with_eeprom(vec![0u8; EEPROM_SIZE])
+    }
+
+    /// Creates a simulator with the given EEPROM contents, which also serve as the factory settings.
+    ///
+    /// The image is truncated or zero padded to [`EEPROM_SIZE`].
+    pub fn with_eeprom(mut eeprom: Vec<u8>) -> Self {
+        eeprom.resize(EEPROM_SIZE, 0);
+
+        Self {
+            state: Mutex::new(State {
+                factory: eeprom.clone(),
+                eeprom,
+                info: DeviceInfo {
+                    product: Some("ATK Simulator".to_string()),
+                    path: "simulator".to_string(),
+                    connection: ConnectionKind::Dongle,
+                    ..Default::default()
+                },
+                battery: [100, 0, 42],
+                version: vec![0x1, 0x0],
+                current_config: 0,
+                mouse_online: true,
+                pair_after: Some(1),
+                pairing: None,
+                faults: VecDeque::new(),
+                replies: VecDeque::new(),
+                received: Vec::new(),
+            }),
+            _cmd: PhantomData,
+        }
+    }
+
+    /// Returns a copy of the emulated EEPROM.
+    pub fn eeprom(&self) -> Vec<u8> {
+        self.state.lock().unwrap().eeprom.clone()
+    }
+
+    /// Writes `data` into the emulated EEPROM at `offset`, bypassing the protocol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data does not fit in the EEPROM.
+    pub fn write_eeprom(&self, offset: usize, data: &[u8]) {
+        self.state.lock().unwrap().eeprom[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    /// Sets the information returned by [`Transport::device_info`].
+    pub fn set_device_info(&self, info: DeviceInfo) {
+        self.state.lock().unwrap().info = info;
+    }
+
+    /// Sets the values answered to `GetBatteryLevel`.
+    ///
+    /// The voltage is expressed in tenths of a volt.
+    pub fn set_battery(&self, level: u8, charging: bool, voltage: u8) {
+        self.state.lock().unwrap().battery = [level, charging as u8, voltage];
+    }
+
+    /// Sets the data answered to `GetMouseVersion`.
+    pub fn set_version(&self, version: &[u8]) {
+        self.state.lock().unwrap().version = version.to_vec();
+    }
+
+    /// Sets whether `GetWirelessMouseOnline` reports the mouse as connected.
+    pub fn set_mouse_online(&self, online: bool) {
+        self.state.lock().unwrap().mouse_online = online;
+    }
+
+    /// Sets how many `GetWirelessDonglePairResult` polls report pairing in progress before the mouse
+    /// pairs and comes online, `None` if pairing never succeeds. Defaults to one poll.
+    pub fn set_pairing(&self, polls: Option<u32>) {
+        self.state.lock().unwrap().pair_after = polls;
+    }
+
+    /// Queues a fault applied to the next reply. Faults are consumed in order, one per reply.
+    pub fn push_fault(&self, fault: Fault) {
+        self.state.lock().unwrap().faults.push_back(fault);
+    }
+
+    /// Queues a report the device sends on its own, e.g. `ReportMouseStatus`.
+    ///
+    /// The report must not include the report ID.
+    pub fn inject_report(&self, report: &[u8]) {
+        let report = [[C::REPORT_ID].as_ref(), report].concat();
+        self.state
+            .lock()
+            .unwrap()
+            .replies
+            .push_back((report, Duration::ZERO));
+    }
+
+    /// Returns every report written to the simulator, including the report ID.
+    pub fn received(&self) -> Vec<Vec<u8>> {
+        self.state.lock().unwrap().received.clone()
+    }
+
+    fn respond(state: &mut State, request: &Command<C>) -> Result<Command<C>, Error> {
+        let mut response = request.clone();
+        response.set_status(STATUS_SUCCESS);
+
+        // Like a receiver, only answer for itself while the mouse is offline
+        if !state.mouse_online && !request.id().is_receiver_command() {
+            response.set_status(STATUS_FAILURE);
+            return Ok(response);
+        }
+
+        match request.id() {
+            CommandId::SetEEPROM => {
+                let range = Self::eeprom_range(request)?;
+                state.eeprom[range].copy_from_slice(&request.data()[..request.data_len()]);
+            }
+            CommandId::GetEEPROM => {
+                let range = Self::eeprom_range(request)?;
+                response.set_data(&state.eeprom[range], 0)?;
+            }
+            CommandId::RestoreFactory => state.eeprom = state.factory.clone(),
+            CommandId::GetBatteryLevel => Self::set_response_data(&mut response, &state.battery)?,
+            CommandId::GetMouseVersion => Self::set_response_data(&mut response, &state.version)?,
+            CommandId::GetWirelessMouseOnline => {
+                Self::set_response_data(&mut response, &[state.mouse_online as u8])?
+            }
+            CommandId::SetWirelessDonglePair => state.pairing = Some(state.pair_after),
+            CommandId::GetWirelessDonglePairResult => {
+                let result = match state.pairing {
+                    Some(Some(0)) => {
+                        state.pairing = None;
+                        state.mouse_online = true;
+                        PAIR_SUCCESS
+                    }
+                    Some(Some(polls)) => {
+                        state.pairing = Some(Some(polls - 1));
+                        PAIR_IN_PROGRESS
+                    }
+                    Some(None) => PAIR_IN_PROGRESS,
+                    None => PAIR_FAILED,
+                };
+                Self::set_response_data(&mut response, &[result])?
+            }
+            CommandId::DongleExitPair => state.pairing = None,
+            CommandId::GetCurrentConfig => {
+                Self::set_response_data(&mut response, &[state.current_config])?
+            }
+            CommandId::SetCurrentConfig => state.current_config = request.data()[0],
+            _ => response.set_status(STATUS_FAILURE),
+        }
+
+        Ok(response)
+    }
+
+    fn eeprom_range(request: &Command<C>) -> Result<std::ops::Range<usize>, Error> {
+        let start = request.eeprom_address().value() as usize;
+        let end = start + request.data_len();
+        if end > EEPROM_SIZE {
+            return Err(Error::InvalidOffset(end));
+        }
+
+        Ok(start..end)
+    }
+
+    fn set_response_data(response: &mut Command<C>, data: &[u8]) -> Result<(), Error> {
+        response.set_data_len(data.len())?;
+        response.set_data(data, 0)
+    }
+}
+
+impl<C: CommandDescriptor> Transport for Simulator<C> {
+    fn write_report(&self, data: &[u8]) -> Result<usize, Error> {
+        let mut state = self.state.lock().unwrap();
+        state.received.push(data.to_vec());
+
+        // Like the hardware, ignore anything that is not a valid command
+        if data.first() != Some(&C::REPORT_ID) {
+            return Ok(data.len());
+        }
+        let request = match Command::<C>::try_from(&data[1..]) {
+            Ok(request) => request,
+            Err(_) => return Ok(data.len()),
+        };
+
+        let fault = state.faults.pop_front();
+        let response = match fault {
+            Some(Fault::DropReply) => return Ok(data.len()),
+            Some(Fault::Reject(status)) => {
+                let mut response = request.clone();
+                response.set_status(status);
+                response
+            }
+            _ => Self::respond(&mut state, &request).unwrap_or_else(|_| {
+                let mut response = request.clone();
+                response.set_status(STATUS_FAILURE);
+                response
+            }),
+        };
+
+        let mut report = [[C::REPORT_ID].as_ref(), response.as_bytes().as_ref()].concat();
+        if fault == Some(Fault::BadChecksum) {
+            report[C::CMD_LEN] ^= 0xff;
+        }
+        let delay = match fault {
+            Some(Fault::Delay(delay)) => delay,
+            _ => Duration::ZERO,
+        };
+
+        state.replies.push_back((report, delay));
+        Ok(data.len())
+    }
+
+    fn read_report(&self, buf: &mut [u8], timeout: Option<Duration>) -> Result<usize, Error> {
+        let mut state = self.state.lock().unwrap();
+        let Some((report, delay)) = state.replies.front_mut() else {
+            return Err(Error::Timeout);
+        };
+
+        if let Some(timeout) = timeout.filter(|&t| t < *delay) {
+            *delay -= timeout;
+            drop(state);
+            std::thread::sleep(timeout);
+            return Err(Error::Timeout);
+        }
+
+        let delay = *delay;
+        let len = report.len().min(buf.len());
+        buf[..len].copy_from_slice(&report[..len]);
+        state.replies.pop_front();
+        drop(state);
+
+        std::thread::sleep(delay);
+        Ok(len)
+    }
+
+    fn get_feature_report(&self, _buf: &mut [u8]) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    fn device_info(&self) -> Result<DeviceInfo, Error> {
+        Ok(self.state.lock().unwrap().info.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eeprom::Eeprom, types::EEPROMAddress};
+
+    fn send(simulator: &Simulator<Eeprom>, command: &Command<Eeprom>) {
+        let report = [[Eeprom::REPORT_ID].as_ref(), command.as_bytes().as_ref()].concat();
+        simulator.write_report(&report).unwrap();
+    }
+
+    fn read(simulator: &Simulator<Eeprom>, timeout: Option<Duration>) -> Result<Vec<u8>, Error> {
+        let mut buf = [0u8; 65];
+        let len = simulator.read_report(&mut buf, timeout)?;
+        assert_eq!(buf[0], Eeprom::REPORT_ID);
+        Ok(buf[1..len].to_vec())
+    }
+
+    fn query() -> Command<Eeprom> {
+        Command::get(EEPROMAddress::CurrentDpi, 2).unwrap()
+    }
+
+    #[test]
+    fn answers_with_valid_frames() {
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.write_eeprom(0x4, &[0x3, 0x52]);
+        send(&simulator, &query());
+
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.status(), STATUS_SUCCESS);
+        assert_eq!(response.data()[..2], [0x3, 0x52]);
+        assert!(matches!(read(&simulator, None), Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn faults_apply_to_one_reply_each_in_order() {
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.push_fault(Fault::DropReply);
+        simulator.push_fault(Fault::BadChecksum);
+        simulator.push_fault(Fault::Reject(0x7));
+
+        send(&simulator, &query());
+        assert!(matches!(read(&simulator, None), Err(Error::Timeout)));
+
+        send(&simulator, &query());
+        assert!(matches!(
+            Command::<Eeprom>::try_from(read(&simulator, None).unwrap()),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        send(&simulator, &query());
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.status(), 0x7);
+
+        send(&simulator, &query());
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.status(), STATUS_SUCCESS);
+    }
+
+    #[test]
+    fn delayed_replies_arrive_on_a_later_read() {
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.push_fault(Fault::Delay(Duration::from_millis(30)));
+        send(&simulator, &query());
+
+        let timeout = Some(Duration::from_millis(20));
+        assert!(matches!(read(&simulator, timeout), Err(Error::Timeout)));
+        assert!(read(&simulator, timeout).is_ok());
+    }
+
+    #[test]
+    fn injected_reports_are_read_before_replies() {
+        let simulator = Simulator::<Eeprom>::new();
+        let mut status = Command::<Eeprom>::default();
+        status.set_id(CommandId::ReportMouseStatus);
+        simulator.inject_report(&status.as_bytes());
+        send(&simulator, &query());
+
+        assert_eq!(read(&simulator, None).unwrap(), status.as_bytes());
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.id(), CommandId::GetEEPROM);
+    }
+
+    #[test]
+    fn ignores_invalid_frames() {
+        let simulator = Simulator::<Eeprom>::new();
+        let mut report = [[Eeprom::REPORT_ID].as_ref(), query().as_bytes().as_ref()].concat();
+        report[Eeprom::CMD_LEN] ^= 0xff;
+        simulator.write_report(&report).unwrap();
+
+        assert!(matches!(read(&simulator, None), Err(Error::Timeout)));
+        assert_eq!(simulator.received(), vec![report]);
+    }
+
+    #[test]
+    fn only_the_receiver_answers_while_offline() {
+        let simulator = Simulator::<Eeprom>::new();
+        simulator.set_mouse_online(false);
+
+        send(&simulator, &query());
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.status(), STATUS_FAILURE);
+
+        let mut online = Command::<Eeprom>::default();
+        online.set_id(CommandId::GetWirelessMouseOnline);
+        send(&simulator, &online);
+        let response = Command::<Eeprom>::try_from(read(&simulator, None).unwrap()).unwrap();
+        assert_eq!(response.status(), STATUS_SUCCESS);
+        assert_eq!(response.data()[0], 0);
+    }
+}
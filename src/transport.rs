@@ -0,0 +1,151 @@
+use crate::{model::DeviceModel, types::Error};
+use hidapi::HidDevice;
+use std::time::Duration;
+
+/// Vendor IDs used by ATK based devices
+pub static ATK_VENDOR_IDS: [u16; 2] = [0x3554, 0x373b];
+
+/// How the device is connected to the host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionKind {
+    /// The mouse is plugged in with a cable
+    Wired,
+    /// The interface belongs to a 2.4 GHz receiver talking to the mouse
+    Dongle,
+    #[default]
+    Unknown,
+}
+
+impl ConnectionKind {
+    /// Guesses the connection kind from the product string of an interface missing from the registry.
+    fn from_product(product: Option<&str>) -> Self {
+        let Some(product) = product.map(str::to_lowercase) else {
+            return ConnectionKind::Unknown;
+        };
+
+        if ["receiver", "dongle", "2.4g"]
+            .iter()
+            .any(|word| product.contains(word))
+        {
+            ConnectionKind::Dongle
+        } else {
+            ConnectionKind::Wired
+        }
+    }
+}
+
+/// Information about the device behind a [`Transport`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// USB interface number, `-1` if unknown
+    pub interface_number: i32,
+    pub usage_page: u16,
+    pub usage: u16,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    pub serial_number: Option<String>,
+    /// Platform specific path of the device, e.g. `/dev/hidraw3` on Linux
+    pub path: String,
+    pub connection: ConnectionKind,
+}
+
+impl DeviceInfo {
+    /// Returns true if the interface looks like an ATK protocol interface, i.e. a vendor defined
+    /// usage page on a device from a known ATK vendor.
+    pub fn is_atk_interface(&self) -> bool {
+        ATK_VENDOR_IDS.contains(&self.vendor_id) && self.usage_page >= 0xff00
+    }
+}
+
+impl From<&hidapi::DeviceInfo> for DeviceInfo {
+    fn from(info: &hidapi::DeviceInfo) -> Self {
+        Self {
+            vendor_id: info.vendor_id(),
+            product_id: info.product_id(),
+            interface_number: info.interface_number(),
+            usage_page: info.usage_page(),
+            usage: info.usage(),
+            manufacturer: info.manufacturer_string().map(str::to_string),
+            product: info.product_string().map(str::to_string),
+            serial_number: info.serial_number().map(str::to_string),
+            path: info.path().to_string_lossy().into_owned(),
+            connection: DeviceModel::find(info.vendor_id(), info.product_id()).map_or_else(
+                || ConnectionKind::from_product(info.product_string()),
+                |model| model.connection,
+            ),
+        }
+    }
+}
+
+/// The channel used by a [`Device`](crate::device::Device) to exchange reports with the hardware.
+///
+/// Implementing this trait allows to drive the protocol over something else than hidapi, e.g. a mock
+/// for tests, a recorder, raw `/dev/hidraw` file I/O or a network bridge.
+pub trait Transport {
+    /// Writes an output report. The first byte of `data` is the report ID.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.
+    fn write_report(&self, data: &[u8]) -> Result<usize, Error>;
+
+    /// Reads an input report into `buf`, waiting at most `timeout` for it to arrive. A timeout of
+    /// `None` blocks until a report arrives. The first byte of the report is the report ID.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` with the number of bytes read, `Ok(0)` is treated like a timeout.
+    /// * `Err(Error::Timeout)` if no report arrived in time.
+    fn read_report(&self, buf: &mut [u8], timeout: Option<Duration>) -> Result<usize, Error>;
+
+    /// Reads a feature report into `buf`. The report ID must be set in the first byte of `buf`.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes read, including the report ID.
+    fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, Error>;
+
+    /// Returns information about the device.
+    fn device_info(&self) -> Result<DeviceInfo, Error>;
+}
+
+/// A [`Transport`] backed by a [hidapi::HidDevice].
+#[derive(Debug)]
+pub struct HidTransport(HidDevice);
+
+impl HidTransport {
+    pub fn new(device: HidDevice) -> Self {
+        Self(device)
+    }
+
+    /// Returns the underlying hidapi device.
+    pub fn hid_device(&self) -> &HidDevice {
+        &self.0
+    }
+}
+
+impl Transport for HidTransport {
+    fn write_report(&self, data: &[u8]) -> Result<usize, Error> {
+        self.0.write(data).map_err(Error::HidError)
+    }
+
+    fn read_report(&self, buf: &mut [u8], timeout: Option<Duration>) -> Result<usize, Error> {
+        let timeout = timeout.map_or(-1, |t| t.as_millis().min(i32::MAX as u128) as i32);
+        match self.0.read_timeout(buf, timeout).map_err(Error::HidError)? {
+            0 => Err(Error::Timeout),
+            bytes_read => Ok(bytes_read),
+        }
+    }
+
+    fn get_feature_report(&self, buf: &mut [u8]) -> Result<usize, Error> {
+        self.0.get_feature_report(buf).map_err(Error::HidError)
+    }
+
+    fn device_info(&self) -> Result<DeviceInfo, Error> {
+        let info = self.0.get_device_info().map_err(Error::HidError)?;
+
+        Ok(DeviceInfo::from(&info))
+    }
+}
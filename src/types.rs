@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 #[derive(Debug)]
 pub enum Error {
     InvalidBufferLength {
@@ -5,6 +7,10 @@ pub enum Error {
         actual: usize,
     },
     InvalidCommandId(u8),
+    #[deprecated(
+        since = "0.2.0",
+        note = "every `u16` is a valid `EEPROMAddress`, this error is never returned"
+    )]
     InvalidEEPROMAddress(u16),
     DataTooLarge(usize),
     InvalidDataLength {
@@ -16,9 +22,29 @@ pub enum Error {
     OffsetNotAligned(usize),
     HidError(hidapi::HidError),
     ParseError(String),
+    ChecksumMismatch {
+        expected: u8,
+        actual: u8,
+    },
+    DeviceRejected {
+        status: u8,
+    },
+    NoMatchingResponse(CommandId),
+    Timeout,
+    InvalidSetting(String),
+    VerificationFailed {
+        address: u16,
+    },
+    ModelMismatch {
+        image: (u16, u16),
+        device: (u16, u16),
+    },
+    IoError(std::io::Error),
+    MouseOffline,
 }
 
 impl std::fmt::Display for Error {
+    #[allow(deprecated)]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let message = match self {
             Error::InvalidBufferLength { expected, actual } => {
@@ -42,6 +68,25 @@ impl std::fmt::Display for Error {
                 offset
             ),
             Error::ParseError(e) => e.clone(),
+            Error::ChecksumMismatch { expected, actual } => format!(
+                "Checksum mismatch: expected {:#04x}, got {:#04x}",
+                expected, actual
+            ),
+            Error::DeviceRejected { status } => {
+                format!("Device rejected the command with status {:#04x}", status)
+            }
+            Error::NoMatchingResponse(id) => format!("No response received for {:?}", id),
+            Error::Timeout => "Timed out waiting for the device".to_string(),
+            Error::InvalidSetting(e) => format!("Invalid setting: {}", e),
+            Error::VerificationFailed { address } => {
+                format!("EEPROM verification failed at {:#06x}", address)
+            }
+            Error::ModelMismatch { image, device } => format!(
+                "Image was taken from {:04x}:{:04x}, the device is {:04x}:{:04x}",
+                image.0, image.1, device.0, device.1
+            ),
+            Error::IoError(e) => e.to_string(),
+            Error::MouseOffline => "The mouse is not connected to the receiver".to_string(),
         };
 
         write!(f, "{}", message)
@@ -50,8 +95,38 @@ impl std::fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
+/// Rejects a setting outside of `range` with `Error::InvalidSetting`, naming it in the message.
+pub(crate) fn check_range(name: &str, value: u8, range: &RangeInclusive<u8>) -> Result<(), Error> {
+    if !range.contains(&value) {
+        return Err(Error::InvalidSetting(format!(
+            "{} must be between {} and {}: {}",
+            name,
+            range.start(),
+            range.end(),
+            value
+        )));
+    }
+
+    Ok(())
+}
+
+/// A color as stored in the EEPROM
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(dead_code)]
 pub enum CommandId {
     /// Not a valid CommandID, used for initialization
@@ -86,20 +161,62 @@ pub enum CommandId {
     ReportMouseUpgradeStatus,
 }
 
+impl CommandId {
+    /// Returns true for the commands answered by the wireless receiver itself, which work even while
+    /// the mouse is offline.
+    pub fn is_receiver_command(self) -> bool {
+        matches!(
+            self,
+            CommandId::GetWirelessMouseOnline
+                | CommandId::SetWirelessDonglePair
+                | CommandId::GetWirelessDonglePairResult
+                | CommandId::DongleExitPair
+        )
+    }
+}
+
 impl TryFrom<u8> for CommandId {
     type Error = Error;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            0x0..=0x1b => unsafe { Ok(std::mem::transmute(value)) },
+            0x0..=0x1b => unsafe { Ok(std::mem::transmute::<u8, CommandId>(value)) },
             _ => Err(Error::InvalidCommandId(value)),
         }
     }
 }
 
-#[repr(u16)]
-#[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
-pub enum EEPROMAddress {
+macro_rules! eeprom_addresses {
+    ($($(#[$meta:meta])* $name:ident = $value:expr,)*) => {
+        #[allow(non_upper_case_globals)]
+        impl EEPROMAddress {
+            $($(#[$meta])* pub const $name: EEPROMAddress = EEPROMAddress($value);)*
+        }
+
+        static ADDRESS_NAMES: &[(EEPROMAddress, &str)] = &[$((EEPROMAddress::$name, stringify!($name)),)*];
+    };
+}
+
+/// An address in the EEPROM of the mouse.
+///
+/// Every known field has a named constant, e.g. `EEPROMAddress::ReportRate`, but any `u16` is a valid
+/// address so that regions without a name, like the inside of a macro slot, can be addressed too.
+/// Use [`EEPROMAddress::value`] or `u16::from` to get the raw address, this used to be an enum
+/// cast with `as u16` before 0.2.0.
+///
+/// # Examples
+///
+/// ```
+/// use libatk_rs::prelude::*;
+///
+/// assert_eq!(EEPROMAddress::from(0x300), EEPROMAddress::Macro0);
+/// assert_eq!(EEPROMAddress::Macro0.name(), Some("Macro0"));
+/// assert_eq!(EEPROMAddress::from(0x310).name(), None);
+/// assert_eq!(format!("{:?}", EEPROMAddress::from(0x310)), "EEPROMAddress(0x0310)");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EEPROMAddress(u16);
+
+eeprom_addresses! {
     ReportRate = 0x0,
     ReportRateCrc = 0x1,
     MaxDpi = 0x2,
@@ -218,21 +335,38 @@ pub enum EEPROMAddress {
     Macro15 = 0x1980,
 }
 
-impl TryFrom<u16> for EEPROMAddress {
-    type Error = Error;
-    fn try_from(value: u16) -> Result<Self, Self::Error> {
-        match value {
-            0x0 | 0x1 | 0x2 | 0x3 | 0x4 | 0x5 | 0xa | 0xb | 0xc | 0x14 | 0x1c | 0x24 | 0x2c
-            | 0x34 | 0x3c | 0x44 | 0x4c | 0x4d | 0x4e | 0x4f | 0x50 | 0x51 | 0x52 | 0x53 | 0x54
-            | 0x55 | 0x56 | 0x57 | 0x58 | 0x59 | 0x5a | 0x5b | 0x5c | 0x5d | 0x5e | 0x5f | 0xa9
-            | 0xaa | 0xab | 0xac | 0xad | 0xae | 0xaf | 0xb0 | 0xb1 | 0xb2 | 0xb3 | 0xb4 | 0xb5
-            | 0xb6 | 0xb7 | 0xb8 | 0xb9 | 0xba | 0xbb | 0xbc | 0x60 | 0x64 | 0x68 | 0x6c | 0x70
-            | 0x74 | 0x78 | 0x7c | 0x80 | 0x84 | 0x88 | 0x8c | 0x90 | 0x94 | 0x98 | 0x9c
-            | 0x100 | 0x120 | 0x140 | 0x160 | 0x180 | 0x1a0 | 0x1c0 | 0x1e0 | 0x200 | 0x220
-            | 0x240 | 0x260 | 0x280 | 0x2a0 | 0x2c0 | 0x2e0 | 0x300 | 0x480 | 0x600 | 0x780
-            | 0x900 | 0xa80 | 0xc00 | 0xd80 | 0xf00 | 0x1080 | 0x1200 | 0x1380 | 0x1500
-            | 0x1680 | 0x1800 | 0x1980 => unsafe { Ok(std::mem::transmute(value)) },
-            _ => Err(Error::InvalidEEPROMAddress(value)),
+impl EEPROMAddress {
+    /// Returns the raw address.
+    pub const fn value(self) -> u16 {
+        self.0
+    }
+
+    /// Returns the name of the field starting at this address, `None` if it has no name.
+    pub fn name(self) -> Option<&'static str> {
+        ADDRESS_NAMES
+            .iter()
+            .find(|(address, _)| *address == self)
+            .map(|&(_, name)| name)
+    }
+}
+
+impl From<u16> for EEPROMAddress {
+    fn from(value: u16) -> Self {
+        EEPROMAddress(value)
+    }
+}
+
+impl From<EEPROMAddress> for u16 {
+    fn from(address: EEPROMAddress) -> Self {
+        address.0
+    }
+}
+
+impl std::fmt::Debug for EEPROMAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "EEPROMAddress({:#06x})", self.0),
         }
     }
 }
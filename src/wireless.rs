@@ -0,0 +1,348 @@
+use crate::{
+    command::Command,
+    device::Device,
+    eeprom::Eeprom,
+    transport::{ConnectionKind, Transport},
+    types::{CommandId, Error},
+};
+use std::ops::ControlFlow;
+use std::time::{Duration, Instant};
+
+/*
+`GetWirelessDonglePairResult` is answered with a single byte:
+┌──────┬─────────────────────┐
+│ 0x00 │ Pairing in progress │
+│ 0x01 │ Paired              │
+│ 0x02 │ Pairing failed      │
+└──────┴─────────────────────┘
+*/
+
+/// Interval between two `GetWirelessDonglePairResult` polls
+pub const PAIR_POLL_INTERVAL: Duration = Duration::from_millis(200);
+/// Interval between two `GetWirelessMouseOnline` polls while waiting for the mouse
+pub const ONLINE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Whether the mouse can currently be talked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The mouse is plugged in with a cable
+    Wired,
+    /// The mouse is connected to the receiver
+    Online,
+    /// The mouse is asleep, switched off or out of range of the receiver
+    Offline,
+}
+
+/// How a pairing attempt ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairingOutcome {
+    /// A mouse was paired with the receiver
+    Paired,
+    /// The mouse was already connected, the receiver was not put into pairing mode
+    AlreadyPaired,
+    /// No mouse was paired before the timeout
+    TimedOut,
+    /// The progress callback stopped the attempt
+    Cancelled,
+    /// The receiver reported that pairing failed
+    Failed,
+}
+
+impl<T: Transport> Device<T> {
+    /// Asks the receiver whether the mouse is connected with `GetWirelessMouseOnline`.
+    pub(crate) fn is_mouse_online(&self) -> Result<bool, Error> {
+        let mut command = Command::<Eeprom>::default();
+        command.set_id(CommandId::GetWirelessMouseOnline);
+
+        Ok(self.execute(command)?.data()[0] != 0)
+    }
+
+    /// Returns whether the mouse is connected, asking the receiver with `GetWirelessMouseOnline`.
+    ///
+    /// Wired mice are reported as [`ConnectionState::Wired`] without sending anything. Unlike the check
+    /// done by [`Device::execute`] after a failed command, a device whose connection is
+    /// [`ConnectionKind::Unknown`] is asked too, as this is an explicit request, the error it answers
+    /// with is returned if it does not understand the query.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::wireless::ConnectionState;
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// assert_eq!(device.get_connection_state().unwrap(), ConnectionState::Online);
+    ///
+    /// device.transport().set_mouse_online(false);
+    /// assert_eq!(device.get_connection_state().unwrap(), ConnectionState::Offline);
+    /// assert!(matches!(device.get_report_rate(), Err(Error::MouseOffline)));
+    /// ```
+    pub fn get_connection_state(&self) -> Result<ConnectionState, Error> {
+        match self.connection() {
+            ConnectionKind::Wired => return Ok(ConnectionState::Wired),
+            ConnectionKind::Dongle | ConnectionKind::Unknown => {}
+        }
+
+        Ok(match self.is_mouse_online()? {
+            true => ConnectionState::Online,
+            false => ConnectionState::Offline,
+        })
+    }
+
+    /// Waits for the mouse to connect to the receiver, for example after waking it up.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` once the mouse is connected, right away for wired mice.
+    /// * `Err(Error::MouseOffline)` if the mouse did not connect before the timeout.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use std::time::Duration;
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// device.transport().set_mouse_online(false);
+    /// assert!(matches!(
+    ///     device.wait_until_online(Duration::from_millis(300)),
+    ///     Err(Error::MouseOffline)
+    /// ));
+    ///
+    /// device.transport().set_mouse_online(true);
+    /// device.wait_until_online(Duration::from_secs(1)).unwrap();
+    /// ```
+    pub fn wait_until_online(&self, timeout: Duration) -> Result<(), Error> {
+        let start = Instant::now();
+        while self.get_connection_state()? == ConnectionState::Offline {
+            if start.elapsed() + ONLINE_POLL_INTERVAL > timeout {
+                return Err(Error::MouseOffline);
+            }
+            std::thread::sleep(ONLINE_POLL_INTERVAL);
+        }
+
+        Ok(())
+    }
+
+    fn execute_receiver_command(&self, id: CommandId) -> Result<Command<Eeprom>, Error> {
+        let mut command = Command::<Eeprom>::default();
+        command.set_id(id);
+
+        self.execute(command)
+    }
+
+    /// Puts the receiver into pairing mode and waits for a mouse to pair.
+    ///
+    /// `progress` is called with the elapsed time before every poll of the pairing result, returning
+    /// `ControlFlow::Break(())` cancels the attempt. The receiver leaves pairing mode when the attempt
+    /// times out, is cancelled, fails or hits an error. Like [`Device::get_connection_state`], a device
+    /// whose connection is [`ConnectionKind::Unknown`] is treated as a receiver.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PairingOutcome)` describing how the attempt ended.
+    /// * `Err(Error::InvalidSetting)` if the device is a wired mouse rather than a receiver.
+    ///
+    /// # Examples
+    /// ```
+    /// # use libatk_rs::prelude::*;
+    /// # use libatk_rs::{eeprom::Eeprom, simulator::Simulator};
+    /// use libatk_rs::wireless::PairingOutcome;
+    /// use std::ops::ControlFlow;
+    /// use std::time::Duration;
+    ///
+    /// let device = Device::from_transport(Simulator::<Eeprom>::new());
+    /// device.transport().set_mouse_online(false);
+    ///
+    /// let outcome = device
+    ///     .pair(Duration::from_secs(5), |elapsed| {
+    ///         println!("Waiting for the mouse... {:?}", elapsed);
+    ///         ControlFlow::Continue(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(outcome, PairingOutcome::Paired);
+    /// assert_eq!(
+    ///     device.pair(Duration::from_secs(5), |_| ControlFlow::Continue(())).unwrap(),
+    ///     PairingOutcome::AlreadyPaired
+    /// );
+    ///
+    /// device.transport().set_mouse_online(false);
+    /// device.transport().set_pairing(None);
+    /// let outcome = device
+    ///     .pair(Duration::from_millis(500), |_| ControlFlow::Continue(()))
+    ///     .unwrap();
+    /// assert_eq!(outcome, PairingOutcome::TimedOut);
+    /// ```
+    pub fn pair(
+        &self,
+        timeout: Duration,
+        mut progress: impl FnMut(Duration) -> ControlFlow<()>,
+    ) -> Result<PairingOutcome, Error> {
+        match self.connection() {
+            ConnectionKind::Wired => {
+                return Err(Error::InvalidSetting(
+                    "Pairing is done through the receiver".to_string(),
+                ))
+            }
+            ConnectionKind::Dongle | ConnectionKind::Unknown => {}
+        }
+        if self.is_mouse_online()? {
+            return Ok(PairingOutcome::AlreadyPaired);
+        }
+
+        self.execute_receiver_command(CommandId::SetWirelessDonglePair)?;
+        let outcome = self.wait_for_pairing(timeout, &mut progress);
+        if !matches!(outcome, Ok(PairingOutcome::Paired)) {
+            // Leave pairing mode even if the attempt failed with an error
+            let exit = self.execute_receiver_command(CommandId::DongleExitPair);
+            if outcome.is_ok() {
+                exit?;
+            }
+        }
+
+        outcome
+    }
+
+    fn wait_for_pairing(
+        &self,
+        timeout: Duration,
+        progress: &mut impl FnMut(Duration) -> ControlFlow<()>,
+    ) -> Result<PairingOutcome, Error> {
+        let start = Instant::now();
+        loop {
+            if progress(start.elapsed()).is_break() {
+                return Ok(PairingOutcome::Cancelled);
+            }
+
+            let response = self.execute_receiver_command(CommandId::GetWirelessDonglePairResult)?;
+            match response.data()[0] {
+                0x0 => {}
+                0x1 => return Ok(PairingOutcome::Paired),
+                0x2 => return Ok(PairingOutcome::Failed),
+                result => {
+                    return Err(Error::ParseError(format!(
+                        "Invalid pairing result: {:#04x}",
+                        result
+                    )))
+                }
+            }
+
+            if start.elapsed() + PAIR_POLL_INTERVAL > timeout {
+                return Ok(PairingOutcome::TimedOut);
+            }
+            std::thread::sleep(PAIR_POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        simulator::{Fault, Simulator},
+        transport::DeviceInfo,
+    };
+
+    fn device() -> Device<Simulator<Eeprom>> {
+        let device = Device::from_transport(Simulator::new());
+        device.transport().set_mouse_online(false);
+        device
+    }
+
+    fn sent(device: &Device<Simulator<Eeprom>>, id: CommandId) -> usize {
+        device
+            .transport()
+            .received()
+            .iter()
+            .filter(|report| report[1] == id as u8)
+            .count()
+    }
+
+    #[test]
+    fn cancelled_pairing_leaves_pairing_mode() {
+        let device = device();
+
+        let outcome = device
+            .pair(Duration::from_secs(5), |_| ControlFlow::Break(()))
+            .unwrap();
+        assert_eq!(outcome, PairingOutcome::Cancelled);
+        assert_eq!(sent(&device, CommandId::SetWirelessDonglePair), 1);
+        assert_eq!(sent(&device, CommandId::GetWirelessDonglePairResult), 0);
+        assert_eq!(sent(&device, CommandId::DongleExitPair), 1);
+    }
+
+    #[test]
+    fn failed_pairing_leaves_pairing_mode() {
+        let device = device();
+        // `SetWirelessDonglePair` is acknowledged but the receiver never enters pairing mode, so the
+        // first poll reports a failure
+        device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        device.transport().push_fault(Fault::Reject(0x0));
+
+        let outcome = device
+            .pair(Duration::from_secs(5), |_| ControlFlow::Continue(()))
+            .unwrap();
+        assert_eq!(outcome, PairingOutcome::Failed);
+        assert_eq!(sent(&device, CommandId::DongleExitPair), 1);
+    }
+
+    #[test]
+    fn errors_while_pairing_leave_pairing_mode() {
+        let device = device();
+        // `GetWirelessMouseOnline` and `SetWirelessDonglePair` go through, the first poll is rejected
+        device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        device.transport().push_fault(Fault::Delay(Duration::ZERO));
+        device.transport().push_fault(Fault::Reject(0x1));
+
+        assert!(matches!(
+            device.pair(Duration::from_secs(5), |_| ControlFlow::Continue(())),
+            Err(Error::DeviceRejected { status: 0x1 })
+        ));
+        assert_eq!(sent(&device, CommandId::DongleExitPair), 1);
+    }
+
+    #[test]
+    fn successful_pairing_stays_paired() {
+        let device = device();
+
+        let outcome = device
+            .pair(Duration::from_secs(5), |_| ControlFlow::Continue(()))
+            .unwrap();
+        assert_eq!(outcome, PairingOutcome::Paired);
+        assert_eq!(sent(&device, CommandId::DongleExitPair), 0);
+    }
+
+    #[test]
+    fn wired_mice_are_not_asked_whether_they_are_online() {
+        let device = device();
+        device.transport().set_device_info(DeviceInfo {
+            connection: ConnectionKind::Wired,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            device.get_connection_state().unwrap(),
+            ConnectionState::Wired
+        );
+        assert!(matches!(
+            device.pair(Duration::from_secs(5), |_| ControlFlow::Continue(())),
+            Err(Error::InvalidSetting(_))
+        ));
+        assert!(device.transport().received().is_empty());
+    }
+
+    #[test]
+    fn unknown_connections_are_asked_like_receivers() {
+        let device = device();
+        device.transport().set_device_info(DeviceInfo {
+            connection: ConnectionKind::Unknown,
+            ..Default::default()
+        });
+
+        assert_eq!(
+            device.get_connection_state().unwrap(),
+            ConnectionState::Offline
+        );
+        assert_eq!(sent(&device, CommandId::GetWirelessMouseOnline), 1);
+    }
+}